@@ -12,15 +12,9 @@ impl syn::parse::Parse for InterfaceImplArgs {
 }
 
 pub fn interface_impl(args: InterfaceImplArgs, item: syn::ItemImpl) -> syn::Result<TokenStream> {
-    let Args {
-        type_,
-        inheritance,
-        pod,
-    } = args.0;
+    let args = args.0;
 
-    let type_ = type_.expect("no type");
-
-    let mut def = ObjectDefinition::new(item, pod, true)?;
+    let mut def = ObjectDefinition::new(item, args.pod, true)?;
 
     let go = go_crate_ident();
     let glib = quote! { #go::glib };
@@ -51,8 +45,7 @@ pub fn interface_impl(args: InterfaceImplArgs, item: syn::ItemImpl) -> syn::Resu
         ..
     } = Output::new(
         &mut def,
-        Some(&type_),
-        &inheritance,
+        &args,
         &signals_path,
         &properties_path,
         &go,
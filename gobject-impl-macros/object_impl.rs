@@ -12,13 +12,9 @@ impl syn::parse::Parse for ObjectImplArgs {
 }
 
 pub fn object_impl(args: ObjectImplArgs, item: syn::ItemImpl) -> syn::Result<TokenStream> {
-    let Args {
-        type_,
-        inheritance,
-        pod,
-    } = args.0;
+    let args = args.0;
 
-    let mut def = ObjectDefinition::new(item, pod, false)?;
+    let mut def = ObjectDefinition::new(item, args.pod, false)?;
 
     let go = go_crate_ident();
     let glib = quote! { #go::glib };
@@ -47,8 +43,7 @@ pub fn object_impl(args: ObjectImplArgs, item: syn::ItemImpl) -> syn::Result<Tok
         public_methods,
     } = Output::new(
         &mut def,
-        type_.as_ref(),
-        &inheritance,
+        &args,
         &signals_path,
         &properties_path,
         &go,
@@ -66,6 +61,22 @@ pub fn object_impl(args: ObjectImplArgs, item: syn::ItemImpl) -> syn::Result<Tok
                 #prop_defs
             }
         };
+        let (history_snapshot, history_record) = if args.history {
+            (
+                Some(quote! { let old = #go::history_snapshot(obj, pspec); }),
+                Some(quote! { #go::history_record(obj, pspec, old); }),
+            )
+        } else {
+            (None, None)
+        };
+        let seal_check = args.seal.then(|| {
+            quote! {
+                if !#go::check_unsealed(obj, pspec) {
+                    #go::reject_property_set();
+                    return;
+                }
+            }
+        });
         let set_property_def = quote! {
             fn #set_property_ident(
                 &self,
@@ -74,6 +85,8 @@ pub fn object_impl(args: ObjectImplArgs, item: syn::ItemImpl) -> syn::Result<Tok
                 value: &#glib::Value,
                 pspec: &#glib::ParamSpec
             ) {
+                #seal_check
+                #history_snapshot
                 match id {
                     #(#prop_set_impls)*
                     _ => unimplemented!(
@@ -86,6 +99,7 @@ pub fn object_impl(args: ObjectImplArgs, item: syn::ItemImpl) -> syn::Result<Tok
                         ).name()
                     )
                 }
+                #history_record
             }
         };
         let property_def = quote! {
@@ -36,6 +36,9 @@ mod keywords {
     syn::custom_keyword!(override_iface);
     syn::custom_keyword!(override_class);
     syn::custom_keyword!(computed);
+    syn::custom_keyword!(persist);
+    syn::custom_keyword!(env);
+    syn::custom_keyword!(debug);
 
     syn::custom_keyword!(construct);
     syn::custom_keyword!(construct_only);
@@ -160,7 +163,7 @@ impl PropertyPermission {
             Self::Deny
         }
     }
-    fn is_allowed(&self) -> bool {
+    pub fn is_allowed(&self) -> bool {
         !matches!(self, Self::Deny)
     }
 }
@@ -190,6 +193,9 @@ pub struct Property {
     pub subtype: Option<syn::Type>,
     pub flags: PropertyFlags,
     pub flag_idents: Vec<syn::Ident>,
+    pub persist: Option<syn::LitBool>,
+    pub env: Option<syn::LitStr>,
+    pub debug: Option<syn::LitBool>,
 }
 
 impl Property {
@@ -267,6 +273,9 @@ impl Property {
             subtype: None,
             flags: PropertyFlags::empty(),
             flag_idents: vec![],
+            persist: None,
+            env: None,
+            debug: None,
         }
     }
     fn parse(field: &mut syn::Field, pod: bool, iface: bool) -> syn::Result<Self> {
@@ -538,6 +547,27 @@ impl Property {
                 }
                 input.parse::<Token![=]>()?;
                 prop.storage = PropertyStorage::Delegate(kw, Box::new(input.parse::<syn::Expr>()?));
+            } else if !iface && lookahead.peek(keywords::persist) {
+                let kw = input.parse::<keywords::persist>()?;
+                if prop.persist.is_some() {
+                    return Err(syn::Error::new_spanned(kw, "Duplicate `persist` attribute"));
+                }
+                input.parse::<Token![=]>()?;
+                prop.persist.replace(input.parse()?);
+            } else if !iface && lookahead.peek(keywords::debug) {
+                let kw = input.parse::<keywords::debug>()?;
+                if prop.debug.is_some() {
+                    return Err(syn::Error::new_spanned(kw, "Duplicate `debug` attribute"));
+                }
+                input.parse::<Token![=]>()?;
+                prop.debug.replace(input.parse()?);
+            } else if !iface && lookahead.peek(keywords::env) {
+                let kw = input.parse::<keywords::env>()?;
+                if prop.env.is_some() {
+                    return Err(syn::Error::new_spanned(kw, "Duplicate `env` attribute"));
+                }
+                input.parse::<Token![=]>()?;
+                prop.env.replace(input.parse()?);
             } else if !iface && lookahead.peek(Token![abstract]) {
                 let kw = input.call(syn::ext::IdentExt::parse_any)?;
                 if !matches!(prop.storage, PropertyStorage::Field(_)) {
@@ -687,6 +717,17 @@ impl Property {
                 ));
             }
         }
+        if let Some(env) = &self.env {
+            if !self
+                .flags
+                .intersects(PropertyFlags::CONSTRUCT | PropertyFlags::CONSTRUCT_ONLY)
+            {
+                return Err(syn::Error::new_spanned(
+                    env,
+                    "`env` requires `construct` or `construct_only`",
+                ));
+            }
+        }
         if self.flags.contains(PropertyFlags::CONSTRUCT_ONLY) {
             if let Some(notify) = &self.no_notify {
                 return Err(syn::Error::new_spanned(
@@ -838,7 +879,7 @@ impl Property {
             PropertyName::Custom(name) => name.span(),
         }
     }
-    fn inner_type(&self, go: &syn::Ident) -> TokenStream {
+    pub fn inner_type(&self, go: &syn::Ident) -> TokenStream {
         let ty = &self.ty;
         if self.is_abstract() || matches!(self.storage, PropertyStorage::Computed(_)) {
             quote! { #ty }
@@ -856,6 +897,9 @@ impl Property {
         } else {
             quote! { self }
         };
+        self.field_storage_of(recv)
+    }
+    fn field_storage_of(&self, recv: TokenStream) -> TokenStream {
         match &self.storage {
             PropertyStorage::Field(field) => quote! { #recv.#field },
             PropertyStorage::Delegate(_, delegate) => quote! { #recv.#delegate },
@@ -867,9 +911,15 @@ impl Property {
             .iter()
             .find_map(|(i, l)| (i == name).then(|| l))
     }
-    fn is_inherited(&self) -> bool {
+    pub fn is_inherited(&self) -> bool {
         self.override_.is_some()
     }
+    pub fn is_persisted(&self) -> bool {
+        self.persist.as_ref().map(|p| p.value).unwrap_or(true)
+    }
+    pub fn is_debugged(&self) -> bool {
+        self.debug.as_ref().map(|p| p.value).unwrap_or(true)
+    }
     fn is_abstract(&self) -> bool {
         matches!(
             self.storage,
@@ -877,7 +927,7 @@ impl Property {
         )
     }
     #[inline]
-    fn getter_name(&self) -> syn::Ident {
+    pub fn getter_name(&self) -> syn::Ident {
         format_ident!("{}", self.name().to_snake_case())
     }
     pub fn get_impl(&self, index: usize, go: &syn::Ident) -> Option<TokenStream> {
@@ -903,19 +953,39 @@ impl Property {
             quote_spanned! { self.span => fn #method_name(&self) -> #ty }
         })
     }
+    pub fn read_expr(
+        &self,
+        index: usize,
+        object_type: &TokenStream,
+        properties_path: &TokenStream,
+        go: &syn::Ident,
+    ) -> TokenStream {
+        if let PropertyPermission::AllowCustom(method) = &self.get {
+            quote! {
+                <#object_type>::#method(#go::glib::Cast::upcast_ref::<#object_type>(self))
+            }
+        } else if self.is_abstract() {
+            let ty = self.inner_type(go);
+            quote! {
+                <Self as #go::ObjectPspecExt>::property_from_pspec(
+                    self,
+                    &#properties_path()[#index]
+                ).get::<#ty>().unwrap()
+            }
+        } else {
+            let field = self.field_storage(Some(object_type), go);
+            quote! { #go::ParamStoreRead::get_owned(&#field) }
+        }
+    }
     pub fn getter_definition(
         &self,
+        index: usize,
         object_type: &TokenStream,
+        properties_path: &TokenStream,
         go: &syn::Ident,
     ) -> Option<TokenStream> {
         self.getter_prototype(go).map(|proto| {
-            let body = if self.is_abstract() {
-                let name = self.name();
-                quote! { <Self as #go::glib::object::ObjectExt>::property(self, #name) }
-            } else {
-                let field = self.field_storage(Some(object_type), go);
-                quote! { #go::ParamStoreRead::get_owned(&#field) }
-            };
+            let body = self.read_expr(index, object_type, properties_path, go);
             quote_spanned! { self.span =>
                 #proto {
                     #![inline]
@@ -955,7 +1025,7 @@ impl Property {
         })
     }
     #[inline]
-    fn setter_name(&self) -> syn::Ident {
+    pub fn setter_name(&self) -> syn::Ident {
         format_ident!("set_{}", self.name().to_snake_case())
     }
     #[inline]
@@ -1024,7 +1094,13 @@ impl Property {
                     #go::ParamStoreWrite::set_value(&#field, &value);
                 }
             };
-            quote_spanned! { self.span => #index => { #body } }
+            let env_override = self.env.as_ref().map(|env| {
+                quote! {
+                    let env_value = #go::construct_env_override(obj, pspec, #env, value);
+                    let value = env_value.as_ref().unwrap_or(value);
+                }
+            });
+            quote_spanned! { self.span => #index => { #env_override #body } }
         })
     }
     pub fn setter_prototype(&self, go: &syn::Ident) -> Option<TokenStream> {
@@ -1045,10 +1121,12 @@ impl Property {
         index: usize,
         object_type: &TokenStream,
         properties_path: &TokenStream,
+        checked: bool,
         go: &syn::Ident,
     ) -> Option<TokenStream> {
         self.setter_prototype(go).map(|proto| {
-            let body = if !self.is_abstract() && self.set_inline.is_some() {
+            // history and sealing are handled in `set_property`, so setters can't bypass it
+            let body = if !self.is_abstract() && self.set_inline.is_some() && !checked {
                 self.inline_set_impl(
                     Some(object_type),
                     || {
@@ -1062,9 +1140,12 @@ impl Property {
                     go,
                 )
             } else {
-                let name = self.name();
                 quote! {
-                    <Self as #go::glib::object::ObjectExt>::set_property(self, #name, value);
+                    <Self as #go::ObjectPspecExt>::set_property_from_pspec(
+                        self,
+                        &#properties_path()[#index],
+                        #go::glib::ToValue::to_value(&value),
+                    );
                 }
             };
             quote_spanned! { self.span =>
@@ -1075,6 +1156,91 @@ impl Property {
             }
         })
     }
+    pub fn batch_setter_definition(&self, recv: &TokenStream, go: &syn::Ident) -> TokenStream {
+        let proto = self.setter_prototype(go).expect("no setter prototype");
+        let method_name = self.setter_name();
+        quote_spanned! { self.span =>
+            pub #proto {
+                <#recv>::#method_name(self.0, value)
+            }
+        }
+    }
+    pub fn transaction_setter_definition(
+        &self,
+        index: usize,
+        object_type: &TokenStream,
+        properties_path: &TokenStream,
+        seal: bool,
+        go: &syn::Ident,
+    ) -> Option<TokenStream> {
+        let stored = matches!(
+            self.storage,
+            PropertyStorage::Field(_) | PropertyStorage::Delegate(_, _)
+        );
+        // custom setters can't be undone by writing storage, so they have no transaction setter
+        let proto = self.setter_prototype(go).filter(|_| {
+            stored
+                && matches!(self.get, PropertyPermission::Allow)
+                && matches!(self.set, PropertyPermission::Allow)
+        })?;
+        let field = self.field_storage_of(quote! {
+            #go::glib::subclass::prelude::ObjectSubclassIsExt::imp(
+                #go::glib::Cast::upcast_ref::<#object_type>(self.0)
+            )
+        });
+        let min = self.find_buildable_prop("minimum");
+        let max = self.find_buildable_prop("maximum");
+        let check = seal.then(|| {
+            quote! {
+                if !#go::check_unsealed(self.0, &#properties_path()[#index]) {
+                    return;
+                }
+            }
+        });
+        let store = quote! {
+            let field = &#field;
+            let old = #go::ParamStoreReadValue::get_value(field);
+            if #go::ParamStoreWriteChanged::set_owned_checked(field, value) {
+                self.1.record(&#properties_path()[#index], move || {
+                    #go::ParamStoreWrite::set_value(field, &old);
+                });
+            }
+        };
+        if min.is_none() && max.is_none() {
+            return Some(quote_spanned! { self.span =>
+                pub #proto {
+                    #check
+                    #store
+                }
+            });
+        }
+        // bounded setters return an error instead of panicking, so `?` rolls the transaction back
+        let range = match max {
+            Some(max) => quote! { #min..=#max },
+            None => quote! { #min.. },
+        };
+        let method_name = self.setter_name();
+        let ty = self.inner_type(go);
+        let check = seal.then(|| {
+            quote! {
+                if !#go::check_unsealed(self.0, &#properties_path()[#index]) {
+                    return Ok(());
+                }
+            }
+        });
+        Some(quote_spanned! { self.span =>
+            pub fn #method_name(&self, value: #ty) -> ::core::result::Result<(), #go::PropertyError> {
+                #check
+                if !(#range).contains(&value) {
+                    return Err(#go::PropertyError::InvalidValue {
+                        name: ::std::string::ToString::to_string(#properties_path()[#index].name()),
+                    });
+                }
+                #store
+                Ok(())
+            }
+        })
+    }
     pub fn pspec_prototype(&self, glib: &TokenStream) -> Option<TokenStream> {
         let method_name = format_ident!("pspec_{}", self.name().to_snake_case());
         Some(quote_spanned! { self.span => fn #method_name() -> &'static #glib::ParamSpec })
@@ -1122,25 +1288,31 @@ impl Property {
             }
         })
     }
-    pub fn connect_prototype(&self, glib: &TokenStream) -> Option<TokenStream> {
+    pub fn connect_prototype(&self, sync: bool, glib: &TokenStream) -> Option<TokenStream> {
         (!self.is_inherited()
             && self.get.is_allowed()
             && !self.flags.contains(PropertyFlags::CONSTRUCT_ONLY)
             && self.no_connect_notify.is_none())
         .then(|| {
             let method_name = format_ident!("connect_{}_notify", self.name().to_snake_case());
+            let send = sync.then(|| quote! { + ::core::marker::Send + ::core::marker::Sync });
             quote_spanned! { self.span =>
-                fn #method_name<F: Fn(&Self) + 'static>(&self, f: F) -> #glib::SignalHandlerId
+                fn #method_name<F: Fn(&Self) #send + 'static>(&self, f: F) -> #glib::SignalHandlerId
             }
         })
     }
-    pub fn connect_definition(&self, glib: &TokenStream) -> Option<TokenStream> {
-        self.connect_prototype(glib).map(|proto| {
+    pub fn connect_definition(&self, sync: bool, glib: &TokenStream) -> Option<TokenStream> {
+        self.connect_prototype(sync, glib).map(|proto| {
             let name = self.name();
+            let connect = if sync {
+                quote! { connect_notify }
+            } else {
+                quote! { connect_notify_local }
+            };
             quote_spanned! { self.span =>
                 #proto {
                     #![inline]
-                    <Self as #glib::object::ObjectExt>::connect_notify_local(
+                    <Self as #glib::object::ObjectExt>::#connect(
                         self,
                         Some(#name),
                         move |recv, _| f(recv),
@@ -1149,4 +1321,75 @@ impl Property {
             }
         })
     }
+    pub fn connect_weak_prototype(&self, sync: bool, glib: &TokenStream) -> Option<TokenStream> {
+        self.connect_prototype(sync, glib).map(|_| {
+            let method_name =
+                format_ident!("connect_{}_notify_with_weak", self.name().to_snake_case());
+            let send = sync.then(|| quote! { + ::core::marker::Send + ::core::marker::Sync });
+            quote_spanned! { self.span =>
+                fn #method_name<O: #glib::ObjectType #send, F: Fn(&Self, &O) #send + 'static>(
+                    &self,
+                    other: &O,
+                    f: F,
+                ) -> #glib::SignalHandlerId
+            }
+        })
+    }
+    pub fn connect_weak_definition(&self, sync: bool, glib: &TokenStream) -> Option<TokenStream> {
+        self.connect_weak_prototype(sync, glib).map(|proto| {
+            let signal = format!("notify::{}", self.name());
+            let new_closure = if sync {
+                quote! { new }
+            } else {
+                quote! { new_local }
+            };
+            quote_spanned! { self.span =>
+                #proto {
+                    let weak = #glib::object::ObjectExt::downgrade(other);
+                    let closure = #glib::RustClosure::#new_closure(move |args| {
+                        if let ::core::option::Option::Some(other) = weak.upgrade() {
+                            f(&args[0].get::<Self>().unwrap(), &other);
+                        }
+                        ::core::option::Option::None
+                    });
+                    #glib::object::ObjectExt::watch_closure(other, &closure);
+                    <Self as #glib::object::ObjectExt>::connect_closure(self, #signal, false, closure)
+                }
+            }
+        })
+    }
+    pub fn stream_prototype(&self, go: &syn::Ident) -> Option<TokenStream> {
+        let glib = quote! { #go::glib };
+        self.getter_prototype(go)
+            .and(self.connect_prototype(false, &glib))
+            .map(|_| {
+                let method_name = format_ident!("{}_stream", self.name().to_snake_case());
+                let ty = self.inner_type(go);
+                quote_spanned! { self.span =>
+                    fn #method_name(&self) -> #go::SignalStream<#ty>
+                }
+            })
+    }
+    pub fn stream_definition(&self, sync: bool, go: &syn::Ident) -> Option<TokenStream> {
+        self.stream_prototype(go).map(|proto| {
+            let name = self.name();
+            let getter = self.getter_name();
+            let connect = if sync {
+                quote! { connect_notify }
+            } else {
+                quote! { connect_notify_local }
+            };
+            quote_spanned! { self.span =>
+                #proto {
+                    #go::SignalStream::new(self, |sender| {
+                        <Self as #go::glib::object::ObjectExt>::#connect(
+                            self,
+                            Some(#name),
+                            move |recv, _| sender.send(recv.#getter()),
+                        )
+                    })
+                }
+            }
+        })
+    }
 }
@@ -1,4 +1,4 @@
-use heck::{ToKebabCase, ToSnakeCase};
+use heck::{ToKebabCase, ToSnakeCase, ToUpperCamelCase};
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote, quote_spanned};
 use std::collections::HashSet;
@@ -10,6 +10,11 @@ pub mod keywords {
     syn::custom_keyword!(name);
     syn::custom_keyword!(emit);
     syn::custom_keyword!(connect);
+    syn::custom_keyword!(connect_after);
+    syn::custom_keyword!(connect_async);
+    syn::custom_keyword!(future);
+    syn::custom_keyword!(propagation);
+    syn::custom_keyword!(accumulator);
 
     syn::custom_keyword!(run_first);
     syn::custom_keyword!(run_last);
@@ -71,10 +76,105 @@ impl SignalFlags {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccumulatorPreset {
+    FirstWins,
+    TrueHandled,
+    Sum,
+    // registers the signal with the deprecated `GValueArray` as its return type, so C callers
+    // and introspection see an array instead of the handler's return type
+    Collect,
+}
+
+impl AccumulatorPreset {
+    fn from_ident(ident: &syn::Ident) -> Option<Self> {
+        Some(match ident.to_string().as_str() {
+            "first_wins" => Self::FirstWins,
+            "true_handled" => Self::TrueHandled,
+            "sum" => Self::Sum,
+            "collect" => Self::Collect,
+            _ => return None,
+        })
+    }
+    fn check(&self, ident: &syn::Ident, output: &syn::ReturnType) -> syn::Result<()> {
+        let ty = match output {
+            syn::ReturnType::Type(_, ty) => ty,
+            _ => {
+                let msg = format!("`{}` accumulator needs a signal with a return type", ident);
+                return Err(syn::Error::new_spanned(ident, msg));
+            }
+        };
+        let is_any = |names: &[&str]| {
+            matches!(
+                &**ty,
+                syn::Type::Path(p) if p.qself.is_none() && names.iter().any(|n| p.path.is_ident(n))
+            )
+        };
+        match self {
+            Self::TrueHandled if !is_any(&["bool"]) => Err(syn::Error::new_spanned(
+                ty,
+                "`true_handled` accumulator needs a signal returning `bool`",
+            )),
+            Self::Sum if !is_any(&["i8", "u8", "i32", "u32", "i64", "u64", "f32", "f64"]) => Err(
+                syn::Error::new_spanned(ty, "`sum` accumulator needs a signal returning a number"),
+            ),
+            _ => Ok(()),
+        }
+    }
+    fn tokens(&self, output: &TokenStream, glib: &TokenStream) -> TokenStream {
+        match self {
+            Self::FirstWins => quote! {
+                *accu = ::core::clone::Clone::clone(value);
+                false
+            },
+            Self::TrueHandled => quote! {
+                let handled = value.get::<bool>().unwrap();
+                *accu = #glib::ToValue::to_value(&handled);
+                !handled
+            },
+            // integers wrap instead of panicking inside the emission
+            Self::Sum if matches!(output.to_string().as_str(), "f32" | "f64") => quote! {
+                let output = accu.get::<#output>().unwrap() + value.get::<#output>().unwrap();
+                *accu = #glib::ToValue::to_value(&output);
+                true
+            },
+            Self::Sum => quote! {
+                let output = accu
+                    .get::<#output>()
+                    .unwrap()
+                    .wrapping_add(value.get::<#output>().unwrap());
+                *accu = #glib::ToValue::to_value(&output);
+                true
+            },
+            // every handler returns a one element array, see `Signal::wrap_return`
+            Self::Collect => quote! {
+                let mut output = accu
+                    .get::<::std::option::Option<#glib::ValueArray>>()
+                    .unwrap()
+                    .unwrap_or_else(|| #glib::ValueArray::new(0));
+                if let ::std::option::Option::Some(values) =
+                    value.get::<::std::option::Option<#glib::ValueArray>>().unwrap()
+                {
+                    for value in values.iter() {
+                        output.append(value);
+                    }
+                }
+                *accu = #glib::ToValue::to_value(&output);
+                true
+            },
+        }
+    }
+}
+
 pub struct SignalAttrs {
     pub flags: SignalFlags,
     pub emit: bool,
     pub connect: bool,
+    pub connect_after: bool,
+    pub connect_async: Option<keywords::connect_async>,
+    pub future: Option<keywords::future>,
+    pub propagation: Option<keywords::propagation>,
+    pub accumulator: Option<(AccumulatorPreset, syn::Ident)>,
     pub name: Option<String>,
 }
 
@@ -84,6 +184,11 @@ impl Parse for SignalAttrs {
             flags: SignalFlags::empty(),
             emit: true,
             connect: true,
+            connect_after: true,
+            connect_async: None,
+            future: None,
+            propagation: None,
+            accumulator: None,
             name: None,
         };
 
@@ -102,6 +207,45 @@ impl Parse for SignalAttrs {
                 }
                 input.parse::<Token![=]>()?;
                 attrs.name = Some(input.parse::<syn::LitStr>()?.value());
+            } else if lookahead.peek(keywords::connect_async) {
+                let kw = input.parse::<keywords::connect_async>()?;
+                if attrs.connect_async.is_some() {
+                    return Err(syn::Error::new_spanned(
+                        kw,
+                        "Duplicate `connect_async` attribute",
+                    ));
+                }
+                attrs.connect_async = Some(kw);
+            } else if lookahead.peek(keywords::future) {
+                let kw = input.parse::<keywords::future>()?;
+                if attrs.future.is_some() {
+                    return Err(syn::Error::new_spanned(kw, "Duplicate `future` attribute"));
+                }
+                attrs.future = Some(kw);
+            } else if lookahead.peek(keywords::propagation) {
+                let kw = input.parse::<keywords::propagation>()?;
+                if attrs.propagation.is_some() {
+                    return Err(syn::Error::new_spanned(
+                        kw,
+                        "Duplicate `propagation` attribute",
+                    ));
+                }
+                attrs.propagation = Some(kw);
+            } else if lookahead.peek(keywords::accumulator) {
+                let kw = input.parse::<keywords::accumulator>()?;
+                if attrs.accumulator.is_some() {
+                    return Err(syn::Error::new_spanned(
+                        kw,
+                        "Duplicate `accumulator` attribute",
+                    ));
+                }
+                input.parse::<Token![=]>()?;
+                let ident = input.parse::<syn::Ident>()?;
+                let preset = AccumulatorPreset::from_ident(&ident).ok_or_else(|| {
+                    let msg = format!("Unknown accumulator preset `{}`", ident);
+                    syn::Error::new_spanned(&ident, msg)
+                })?;
+                attrs.accumulator = Some((preset, ident));
             } else if lookahead.peek(Token![!]) {
                 input.parse::<Token![!]>()?;
                 let lookahead = input.lookahead1();
@@ -117,6 +261,15 @@ impl Parse for SignalAttrs {
                         return Err(syn::Error::new_spanned(kw, "Duplicate `connect` attribute"));
                     }
                     attrs.connect = false;
+                } else if lookahead.peek(keywords::connect_after) {
+                    let kw = input.parse::<keywords::connect_after>()?;
+                    if !attrs.connect_after {
+                        return Err(syn::Error::new_spanned(
+                            kw,
+                            "Duplicate `connect_after` attribute",
+                        ));
+                    }
+                    attrs.connect_after = false;
                 } else {
                     return Err(lookahead.error());
                 }
@@ -144,6 +297,12 @@ impl Parse for SignalAttrs {
                 input.parse::<Token![,]>()?;
             }
         }
+        if let Some(kw) = attrs.future.filter(|_| !attrs.connect) {
+            return Err(syn::Error::new_spanned(
+                kw,
+                "`future` can't be used with `!connect`",
+            ));
+        }
         Ok(attrs)
     }
 }
@@ -155,6 +314,11 @@ pub struct Signal {
     pub interface: bool,
     pub emit: bool,
     pub connect: bool,
+    pub connect_after: bool,
+    pub connect_async: bool,
+    pub future: bool,
+    pub propagation: bool,
+    pub accumulator_preset: Option<AccumulatorPreset>,
     pub handler: Option<syn::ImplItemMethod>,
     pub accumulator: Option<syn::ImplItemMethod>,
 }
@@ -229,6 +393,51 @@ impl Signal {
                         signal.flags = signal_attrs.flags;
                         signal.emit = signal_attrs.emit;
                         signal.connect = signal_attrs.connect;
+                        signal.connect_after = signal_attrs.connect && signal_attrs.connect_after;
+                        signal.future = signal_attrs.future.is_some();
+                        if let Some(kw) = &signal_attrs.future {
+                            if !matches!(method.sig.output, syn::ReturnType::Default) {
+                                return Err(syn::Error::new_spanned(
+                                    kw,
+                                    "`future` can't be used on a signal with a return value",
+                                ));
+                            }
+                        }
+                        if let Some(kw) = &signal_attrs.connect_async {
+                            if !matches!(method.sig.output, syn::ReturnType::Default) {
+                                return Err(syn::Error::new_spanned(
+                                    kw,
+                                    "`connect_async` can't be used on a signal with a return value",
+                                ));
+                            }
+                        }
+                        signal.connect_async = signal_attrs.connect_async.is_some();
+                        if let Some(kw) = &signal_attrs.propagation {
+                            let is_bool = match &method.sig.output {
+                                syn::ReturnType::Type(_, ty) => matches!(
+                                    &**ty,
+                                    syn::Type::Path(p) if p.qself.is_none() && p.path.is_ident("bool")
+                                ),
+                                _ => false,
+                            };
+                            if !is_bool {
+                                return Err(syn::Error::new_spanned(
+                                    kw,
+                                    "`propagation` can only be used on a signal returning `bool`",
+                                ));
+                            }
+                        }
+                        signal.propagation = signal_attrs.propagation.is_some();
+                        if let Some((preset, ident)) = &signal_attrs.accumulator {
+                            if signal.propagation {
+                                return Err(syn::Error::new_spanned(
+                                    ident,
+                                    "`propagation` signals always use the `true_handled` accumulator",
+                                ));
+                            }
+                            preset.check(ident, &method.sig.output)?;
+                        }
+                        signal.accumulator_preset = signal_attrs.accumulator.map(|(p, _)| p);
                         signal.interface = is_interface;
                         signal
                     };
@@ -288,6 +497,21 @@ impl Signal {
                         "Signal with accumulator must have return type",
                     ));
                 }
+                if let (Some(acc), Some(_)) = (&signal.accumulator, signal.accumulator_preset) {
+                    return Err(syn::Error::new_spanned(
+                        acc,
+                        format!(
+                            "Signal `{}` already has an accumulator preset",
+                            signal.ident
+                        ),
+                    ));
+                }
+                if let Some(acc) = signal.accumulator.as_ref().filter(|_| signal.propagation) {
+                    return Err(syn::Error::new_spanned(
+                        acc,
+                        "`propagation` signals always use the `true_handled` accumulator",
+                    ));
+                }
             } else {
                 let acc = signal.accumulator.as_ref().expect("no accumulator");
                 return Err(syn::Error::new_spanned(
@@ -307,6 +531,11 @@ impl Signal {
             interface: false,
             emit: true,
             connect: true,
+            connect_after: true,
+            connect_async: false,
+            future: false,
+            propagation: false,
+            accumulator_preset: None,
             handler: None,
             accumulator: None,
         }
@@ -396,6 +625,7 @@ impl Signal {
             }
         });
         let class_handler = (!handler.block.stmts.is_empty()).then(|| {
+            let wrap_return = self.wrap_return(glib);
             let arg_names = self.arg_names();
             let args_unwrap = self.args_unwrap(Some(self_ty), object_type, true, glib);
             let method_name = &handler.sig.ident;
@@ -403,14 +633,12 @@ impl Signal {
                 let builder = builder.class_handler(|_, args| {
                     #(#args_unwrap)*
                     let ret = #self_ty::#method_name(#(#arg_names),*);
+                    #wrap_return
                     #glib::closure::ToClosureReturnValue::to_closure_return_value(&ret)
                 });
             }
         });
-        let output = match &handler.sig.output {
-            syn::ReturnType::Type(_, ty) => quote! { #ty },
-            _ => quote! { () },
-        };
+        let output = self.return_type(glib);
         let accumulator = accumulator.as_ref().map(|method| {
             let ident = &method.sig.ident;
             let call_args = if method.sig.inputs.len() == 2 {
@@ -429,6 +657,19 @@ impl Signal {
                 });
             }
         });
+        let preset = self
+            .accumulator_preset
+            .or_else(|| self.propagation.then_some(AccumulatorPreset::TrueHandled));
+        let accumulator = accumulator.or_else(|| {
+            preset.map(|preset| {
+                let body = preset.tokens(&output, glib);
+                quote! {
+                    let builder = builder.accumulator(|_hint, accu, value| {
+                        #body
+                    });
+                }
+            })
+        });
         let flags = (!flags.is_empty()).then(|| {
             let flags = flags.tokens(glib);
             quote! { let builder = builder.flags(#flags); }
@@ -450,6 +691,29 @@ impl Signal {
             }
         }
     }
+    fn collects(&self) -> bool {
+        self.accumulator_preset == Some(AccumulatorPreset::Collect)
+    }
+    // the type the signal is registered with, which differs from the handler's return type
+    // for `collect`
+    fn return_type(&self, glib: &TokenStream) -> TokenStream {
+        match &self.handler.as_ref().unwrap().sig.output {
+            syn::ReturnType::Type(_, _) if self.collects() => quote! { #glib::ValueArray },
+            syn::ReturnType::Type(_, ty) => quote! { #ty },
+            _ => quote! { () },
+        }
+    }
+    fn wrap_return(&self, glib: &TokenStream) -> Option<TokenStream> {
+        self.collects().then(|| {
+            quote! {
+                let ret = {
+                    let mut array = #glib::ValueArray::new(1);
+                    array.append(&#glib::ToValue::to_value(&ret));
+                    array
+                };
+            }
+        })
+    }
     pub fn handler_definition(&self) -> Option<TokenStream> {
         let handler = self.handler.as_ref().unwrap();
         if !handler.block.stmts.is_empty() {
@@ -502,7 +766,12 @@ impl Signal {
     }
     pub fn emit_prototype(&self, glib: &TokenStream) -> TokenStream {
         let handler = self.handler.as_ref().unwrap();
-        let output = &handler.sig.output;
+        let output = match &handler.sig.output {
+            syn::ReturnType::Type(_, ty) if self.collects() => {
+                quote! { -> ::std::vec::Vec<#ty> }
+            }
+            output => quote! { #output },
+        };
         let method_name = format_ident!("emit_{}", self.name.to_snake_case());
         let arg_defs = self.emit_arg_defs();
         let details_arg = self
@@ -553,6 +822,14 @@ impl Signal {
         } else {
             emit
         };
+        let body = match &handler.sig.output {
+            syn::ReturnType::Type(_, ty) if self.collects() => quote! {
+                let ret: ::std::option::Option<#glib::ValueArray> = #body;
+                ret.map(|ret| ret.iter().map(|value| value.get::<#ty>().unwrap()).collect())
+                    .unwrap_or_default()
+            },
+            _ => body,
+        };
         quote_spanned! { handler.span() =>
             #proto {
                 #![inline]
@@ -560,10 +837,76 @@ impl Signal {
             }
         }
     }
-    pub fn connect_prototype(&self, glib: &TokenStream) -> TokenStream {
-        let method_name = format_ident!("connect_{}", self.name.to_snake_case());
+    pub fn stop_emission_prototype(&self, detailed: bool, glib: &TokenStream) -> TokenStream {
+        let (method_name, details_arg) = if detailed {
+            (
+                format_ident!("stop_{}_emission_detailed", self.name.to_snake_case()),
+                Some(quote! { details: #glib::Quark }),
+            )
+        } else {
+            (
+                format_ident!("stop_{}_emission", self.name.to_snake_case()),
+                None,
+            )
+        };
+        quote_spanned! { self.handler.as_ref().unwrap().span() =>
+            fn #method_name(&self, #details_arg)
+        }
+    }
+    pub fn stop_emission_definition(
+        &self,
+        index: usize,
+        detailed: bool,
+        signals_path: &TokenStream,
+        glib: &TokenStream,
+    ) -> TokenStream {
+        let proto = self.stop_emission_prototype(detailed, glib);
+        let details = if detailed {
+            quote! { ::std::option::Option::Some(details) }
+        } else {
+            quote! { ::std::option::Option::None }
+        };
+        quote_spanned! { self.handler.as_ref().unwrap().span() =>
+            #proto {
+                #![inline]
+                <Self as #glib::object::ObjectExt>::stop_signal_emission(
+                    self,
+                    #signals_path()[#index].signal_id(),
+                    #details,
+                );
+            }
+        }
+    }
+    pub fn handler_id_name(&self, type_name: &syn::Ident) -> syn::Ident {
+        format_ident!("{}{}HandlerId", type_name, self.name.to_upper_camel_case())
+    }
+    pub fn handler_id_definition(&self, type_name: &syn::Ident, glib: &TokenStream) -> TokenStream {
+        let name = self.handler_id_name(type_name);
+        quote_spanned! { self.handler.as_ref().unwrap().span() =>
+            #[derive(Debug, PartialEq, Eq)]
+            pub struct #name(#glib::SignalHandlerId);
+            impl ::core::convert::From<#name> for #glib::SignalHandlerId {
+                fn from(id: #name) -> Self {
+                    id.0
+                }
+            }
+        }
+    }
+    fn connect_signature(
+        &self,
+        method_name: syn::Ident,
+        sync: bool,
+        weak: bool,
+        ret: TokenStream,
+        glib: &TokenStream,
+    ) -> TokenStream {
         let handler = self.handler.as_ref().unwrap();
-        let output = &handler.sig.output;
+        let output = if self.propagation {
+            quote! { -> #glib::signal::Inhibit }
+        } else {
+            let output = &handler.sig.output;
+            quote! { #output }
+        };
         let input_types = self.inputs().skip(1).map(|arg| match arg {
             syn::FnArg::Typed(t) => &t.ty,
             _ => unimplemented!(),
@@ -572,41 +915,409 @@ impl Signal {
             .flags
             .contains(SignalFlags::DETAILED)
             .then(|| quote! { details: ::std::option::Option<#glib::Quark>, });
+        let send = sync.then(|| quote! { + ::core::marker::Send + ::core::marker::Sync });
+        let (other_param, other_type, other_arg) = if weak {
+            (
+                Some(quote! { O: #glib::ObjectType #send, }),
+                Some(quote! { &O, }),
+                Some(quote! { other: &O, }),
+            )
+        } else {
+            (None, None, None)
+        };
         quote_spanned! { handler.span() =>
-            fn #method_name<F: Fn(&Self, #(#input_types),*) #output + 'static>(
+            fn #method_name<
+                #other_param
+                F: Fn(&Self, #other_type #(#input_types),*) #output #send + 'static
+            >(
                 &self,
                 #details_arg
+                #other_arg
                 f: F,
-            ) -> #glib::SignalHandlerId
+            ) -> #ret
         }
     }
-    pub fn connect_definition(
+    pub fn connect_prototype(
         &self,
-        index: usize,
-        signals_path: &TokenStream,
+        after: bool,
+        typed: bool,
+        sync: bool,
+        type_name: &syn::Ident,
+        glib: &TokenStream,
+    ) -> TokenStream {
+        let method_name = format_ident!(
+            "connect_{}{}{}",
+            self.name.to_snake_case(),
+            if after { "_after" } else { "" },
+            if typed { "_typed" } else { "" },
+        );
+        let ret = self.connect_return(typed, type_name, glib);
+        self.connect_signature(method_name, sync, false, ret, glib)
+    }
+    // every connect variant returns a plain id, and has a `_typed` twin returning the id type
+    fn connect_return(
+        &self,
+        typed: bool,
+        type_name: &syn::Ident,
         glib: &TokenStream,
     ) -> TokenStream {
-        let proto = self.connect_prototype(glib);
+        if typed {
+            let id_name = self.handler_id_name(type_name);
+            quote! { #id_name }
+        } else {
+            quote! { #glib::SignalHandlerId }
+        }
+    }
+    fn handler_call(&self, weak: bool, glib: &TokenStream) -> TokenStream {
         let handler = self.handler.as_ref().unwrap();
         let arg_names = self.arg_names().skip(1);
         let args_unwrap = self.args_unwrap(None, None, false, glib).skip(1);
-
-        let details = if self.flags.contains(SignalFlags::DETAILED) {
+        let other = weak.then(|| quote! { &other, });
+        let call = match &handler.sig.output {
+            syn::ReturnType::Type(_, _) => {
+                let wrap_return = self.wrap_return(glib);
+                quote! {
+                    let ret = f(&recv, #other #(#arg_names),*);
+                    #wrap_return
+                    #glib::closure::ToClosureReturnValue::to_closure_return_value(&ret)
+                }
+            }
+            _ => quote! {
+                f(&recv, #other #(#arg_names),*);
+                ::core::option::Option::None
+            },
+        };
+        quote! {
+            let recv = args[0].get::<Self>().unwrap();
+            #(#args_unwrap)*
+            #call
+        }
+    }
+    fn details_expr(&self) -> TokenStream {
+        if self.flags.contains(SignalFlags::DETAILED) {
             quote! { details }
         } else {
             quote! { ::std::option::Option::None }
+        }
+    }
+    pub fn connect_definition(
+        &self,
+        index: usize,
+        after: bool,
+        sync: bool,
+        type_name: &syn::Ident,
+        signals_path: &TokenStream,
+        glib: &TokenStream,
+    ) -> TokenStream {
+        let proto = self.connect_prototype(after, false, sync, type_name, glib);
+        let connect = if sync {
+            quote! { connect_id }
+        } else {
+            quote! { connect_local_id }
         };
-
-        let unwrap = match &handler.sig.output {
-            syn::ReturnType::Type(_, _) => quote! {
-                #glib::closure::ToClosureReturnValue::to_closure_return_value(&_ret)
-            },
+        let details = self.details_expr();
+        let call = self.handler_call(false, glib);
+        quote_spanned! { self.handler.as_ref().unwrap().span() =>
+            #proto {
+                #![inline]
+                <Self as #glib::object::ObjectExt>::#connect(
+                    self,
+                    #signals_path()[#index].signal_id(),
+                    #details,
+                    #after,
+                    move |args| {
+                        #call
+                    },
+                )
+            }
+        }
+    }
+    pub fn typed_definition(
+        &self,
+        after: bool,
+        weak: bool,
+        sync: bool,
+        type_name: &syn::Ident,
+        glib: &TokenStream,
+    ) -> TokenStream {
+        let (proto, connect) = if weak {
+            (
+                self.weak_prototype(true, sync, type_name, glib),
+                format_ident!("connect_{}_with_weak", self.name.to_snake_case()),
+            )
+        } else {
+            (
+                self.connect_prototype(after, true, sync, type_name, glib),
+                format_ident!(
+                    "connect_{}{}",
+                    self.name.to_snake_case(),
+                    if after { "_after" } else { "" },
+                ),
+            )
+        };
+        let id_name = self.handler_id_name(type_name);
+        let details = self
+            .flags
+            .contains(SignalFlags::DETAILED)
+            .then(|| quote! { details, });
+        let other = weak.then(|| quote! { other, });
+        quote_spanned! { self.handler.as_ref().unwrap().span() =>
+            #proto {
+                #id_name(Self::#connect(self, #details #other f))
+            }
+        }
+    }
+    pub fn weak_prototype(
+        &self,
+        typed: bool,
+        sync: bool,
+        type_name: &syn::Ident,
+        glib: &TokenStream,
+    ) -> TokenStream {
+        let method_name = format_ident!(
+            "connect_{}_with_weak{}",
+            self.name.to_snake_case(),
+            if typed { "_typed" } else { "" },
+        );
+        let ret = self.connect_return(typed, type_name, glib);
+        self.connect_signature(method_name, sync, true, ret, glib)
+    }
+    pub fn weak_definition(
+        &self,
+        index: usize,
+        sync: bool,
+        type_name: &syn::Ident,
+        signals_path: &TokenStream,
+        glib: &TokenStream,
+    ) -> TokenStream {
+        let proto = self.weak_prototype(false, sync, type_name, glib);
+        let new_closure = if sync {
+            quote! { new }
+        } else {
+            quote! { new_local }
+        };
+        let details = self.details_expr();
+        let call = self.handler_call(true, glib);
+        // the closure is invalidated and disconnected when `other` goes away, a zeroed return
+        // value covers emissions that race with that
+        let fallback = match &self.handler.as_ref().unwrap().sig.output {
+            syn::ReturnType::Type(_, _) => {
+                let ty = self.return_type(glib);
+                quote! {
+                    ::core::option::Option::Some(#glib::Value::from_type(
+                        <#ty as #glib::StaticType>::static_type()
+                    ))
+                }
+            }
             _ => quote! { ::core::option::Option::None },
         };
-        quote_spanned! { handler.span() =>
+        quote_spanned! { self.handler.as_ref().unwrap().span() =>
             #proto {
-                #![inline]
-                <Self as #glib::object::ObjectExt>::connect_local_id(
+                let weak = #glib::object::ObjectExt::downgrade(other);
+                let closure = #glib::RustClosure::#new_closure(move |args| {
+                    let other = match weak.upgrade() {
+                        ::core::option::Option::Some(other) => other,
+                        ::core::option::Option::None => return #fallback,
+                    };
+                    #call
+                });
+                #glib::object::ObjectExt::watch_closure(other, &closure);
+                <Self as #glib::object::ObjectExt>::connect_closure_id(
+                    self,
+                    #signals_path()[#index].signal_id(),
+                    #details,
+                    false,
+                    closure,
+                )
+            }
+        }
+    }
+    pub fn scoped_prototype(&self, sync: bool, go: &syn::Ident) -> TokenStream {
+        let method_name = format_ident!("connect_{}_scoped", self.name.to_snake_case());
+        let glib = quote! { #go::glib };
+        self.connect_signature(
+            method_name,
+            sync,
+            false,
+            quote! { #go::SignalHandlerGuard },
+            &glib,
+        )
+    }
+    pub fn scoped_definition(&self, sync: bool, go: &syn::Ident) -> TokenStream {
+        let proto = self.scoped_prototype(sync, go);
+        let connect = format_ident!("connect_{}", self.name.to_snake_case());
+        let details = self
+            .flags
+            .contains(SignalFlags::DETAILED)
+            .then(|| quote! { details, });
+        quote_spanned! { self.handler.as_ref().unwrap().span() =>
+            #proto {
+                #go::SignalHandlerGuard::new(self, Self::#connect(self, #details f))
+            }
+        }
+    }
+    pub fn disconnect_prototype(&self, type_name: &syn::Ident) -> TokenStream {
+        let method_name = format_ident!("disconnect_{}", self.name.to_snake_case());
+        let id_name = self.handler_id_name(type_name);
+        quote_spanned! { self.handler.as_ref().unwrap().span() =>
+            fn #method_name(&self, id: #id_name)
+        }
+    }
+    pub fn disconnect_definition(&self, type_name: &syn::Ident, glib: &TokenStream) -> TokenStream {
+        let proto = self.disconnect_prototype(type_name);
+        quote_spanned! { self.handler.as_ref().unwrap().span() =>
+            #proto {
+                <Self as #glib::object::ObjectExt>::disconnect(self, id.0);
+            }
+        }
+    }
+    pub fn block_prototype(&self, type_name: &syn::Ident, go: &syn::Ident) -> TokenStream {
+        let method_name = format_ident!("block_{}", self.name.to_snake_case());
+        let id_name = self.handler_id_name(type_name);
+        quote_spanned! { self.handler.as_ref().unwrap().span() =>
+            fn #method_name<'a>(&'a self, id: &'a #id_name) -> #go::SignalBlockGuard<'a>
+        }
+    }
+    pub fn block_definition(&self, type_name: &syn::Ident, go: &syn::Ident) -> TokenStream {
+        let proto = self.block_prototype(type_name, go);
+        quote_spanned! { self.handler.as_ref().unwrap().span() =>
+            #proto {
+                #go::SignalBlockGuard::new(self, &id.0)
+            }
+        }
+    }
+    fn listener_prototype(
+        &self,
+        suffix: &str,
+        ty: &TokenStream,
+        go: &syn::Ident,
+    ) -> Option<TokenStream> {
+        // a listener can't supply a return value without changing the emission result
+        let handler = self.handler.as_ref().unwrap();
+        matches!(handler.sig.output, syn::ReturnType::Default).then(|| {
+            let method_name = format_ident!("{}_{}", self.name.to_snake_case(), suffix);
+            let input_types = self.inputs().skip(1).map(|arg| match arg {
+                syn::FnArg::Typed(t) => &t.ty,
+                _ => unimplemented!(),
+            });
+            let details_arg = self
+                .flags
+                .contains(SignalFlags::DETAILED)
+                .then(|| quote! { details: ::std::option::Option<#go::glib::Quark> });
+            quote_spanned! { handler.span() =>
+                fn #method_name(&self, #details_arg) -> #ty<(#(#input_types,)*)>
+            }
+        })
+    }
+    fn listener_definition(
+        &self,
+        index: usize,
+        suffix: &str,
+        ty: &TokenStream,
+        sync: bool,
+        signals_path: &TokenStream,
+        go: &syn::Ident,
+    ) -> Option<TokenStream> {
+        self.listener_prototype(suffix, ty, go).map(|proto| {
+            let glib = quote! { #go::glib };
+            let connect = if sync {
+                quote! { connect_id }
+            } else {
+                quote! { connect_local_id }
+            };
+            let details = self.details_expr();
+            let arg_names = self.arg_names().skip(1);
+            let args_unwrap = self.args_unwrap(None, None, false, &glib).skip(1);
+            let args = if self.inputs().count() > 1 {
+                quote! { args }
+            } else {
+                quote! { _ }
+            };
+            quote_spanned! { self.handler.as_ref().unwrap().span() =>
+                #proto {
+                    #ty::new(self, |sender| {
+                        <Self as #glib::object::ObjectExt>::#connect(
+                            self,
+                            #signals_path()[#index].signal_id(),
+                            #details,
+                            false,
+                            move |#args| {
+                                #(#args_unwrap)*
+                                sender.send((#(#arg_names,)*));
+                                ::core::option::Option::None
+                            },
+                        )
+                    })
+                }
+            }
+        })
+    }
+    pub fn future_prototype(&self, go: &syn::Ident) -> Option<TokenStream> {
+        self.listener_prototype("future", &quote! { #go::SignalFuture }, go)
+    }
+    pub fn future_definition(
+        &self,
+        index: usize,
+        sync: bool,
+        signals_path: &TokenStream,
+        go: &syn::Ident,
+    ) -> Option<TokenStream> {
+        let ty = quote! { #go::SignalFuture };
+        self.listener_definition(index, "future", &ty, sync, signals_path, go)
+    }
+    pub fn stream_prototype(&self, go: &syn::Ident) -> Option<TokenStream> {
+        self.listener_prototype("stream", &quote! { #go::SignalStream }, go)
+    }
+    pub fn stream_definition(
+        &self,
+        index: usize,
+        sync: bool,
+        signals_path: &TokenStream,
+        go: &syn::Ident,
+    ) -> Option<TokenStream> {
+        let ty = quote! { #go::SignalStream };
+        self.listener_definition(index, "stream", &ty, sync, signals_path, go)
+    }
+    pub fn async_prototype(&self, type_name: &syn::Ident, glib: &TokenStream) -> TokenStream {
+        let method_name = format_ident!("connect_{}_async", self.name.to_snake_case());
+        let id_name = self.handler_id_name(type_name);
+        let input_types = self.inputs().skip(1).map(|arg| match arg {
+            syn::FnArg::Typed(t) => &t.ty,
+            _ => unimplemented!(),
+        });
+        let details_arg = self
+            .flags
+            .contains(SignalFlags::DETAILED)
+            .then(|| quote! { details: ::std::option::Option<#glib::Quark>, });
+        quote_spanned! { self.handler.as_ref().unwrap().span() =>
+            fn #method_name<
+                Fut: ::std::future::Future<Output = ()> + 'static,
+                F: Fn(&Self, #(#input_types),*) -> Fut + 'static
+            >(
+                &self,
+                #details_arg
+                f: F,
+            ) -> #id_name
+        }
+    }
+    pub fn async_definition(
+        &self,
+        index: usize,
+        type_name: &syn::Ident,
+        signals_path: &TokenStream,
+        go: &syn::Ident,
+    ) -> TokenStream {
+        let glib = quote! { #go::glib };
+        let proto = self.async_prototype(type_name, &glib);
+        let id_name = self.handler_id_name(type_name);
+        let details = self.details_expr();
+        let arg_names = self.arg_names().skip(1);
+        let args_unwrap = self.args_unwrap(None, None, false, &glib).skip(1);
+        // futures are spawned with `spawn_local`, so this is never a `Send` handler
+        quote_spanned! { self.handler.as_ref().unwrap().span() =>
+            #proto {
+                let tasks = #go::AsyncHandler::default();
+                #id_name(<Self as #glib::object::ObjectExt>::connect_local_id(
                     self,
                     #signals_path()[#index].signal_id(),
                     #details,
@@ -614,11 +1325,69 @@ impl Signal {
                     move |args| {
                         let recv = args[0].get::<Self>().unwrap();
                         #(#args_unwrap)*
-                        let _ret = f(&recv, #(#arg_names),*);
-                        #unwrap
+                        tasks.spawn(f(&recv, #(#arg_names),*));
+                        ::core::option::Option::None
                     },
-                )
+                ))
             }
         }
     }
+    pub fn emission_hook_prototype(&self, go: &syn::Ident) -> Option<TokenStream> {
+        // GObject refuses hooks on `no_hooks` signals
+        (!self.flags.contains(SignalFlags::NO_HOOKS)).then(|| {
+            let method_name = format_ident!("add_{}_emission_hook", self.name.to_snake_case());
+            let input_types = self.inputs().skip(1).map(|arg| match arg {
+                syn::FnArg::Typed(t) => &t.ty,
+                _ => unimplemented!(),
+            });
+            let details_arg = self
+                .flags
+                .contains(SignalFlags::DETAILED)
+                .then(|| quote! { details: ::std::option::Option<#go::glib::Quark>, });
+            quote_spanned! { self.handler.as_ref().unwrap().span() =>
+                fn #method_name<
+                    F: Fn(&Self, #(#input_types),*) -> bool
+                        + ::core::marker::Send
+                        + ::core::marker::Sync
+                        + 'static
+                >(
+                    #details_arg
+                    f: F,
+                ) -> #go::EmissionHookId
+                where
+                    Self: Sized
+            }
+        })
+    }
+    pub fn emission_hook_definition(
+        &self,
+        index: usize,
+        signals_path: &TokenStream,
+        go: &syn::Ident,
+    ) -> Option<TokenStream> {
+        self.emission_hook_prototype(go).map(|proto| {
+            let glib = quote! { #go::glib };
+            let details = self.details_expr();
+            let arg_names = self.arg_names().skip(1);
+            let args_unwrap = self.args_unwrap(None, None, false, &glib).skip(1);
+            // hooks see emissions from every instance of the signal's class, including ones
+            // that aren't a `Self` when called through a subclass, those are skipped
+            quote_spanned! { self.handler.as_ref().unwrap().span() =>
+                #proto {
+                    #go::add_emission_hook(
+                        #signals_path()[#index].signal_id(),
+                        #details,
+                        move |args| {
+                            let recv = match args[0].get::<Self>() {
+                                ::core::result::Result::Ok(recv) => recv,
+                                ::core::result::Result::Err(_) => return true,
+                            };
+                            #(#args_unwrap)*
+                            f(&recv, #(#arg_names),*)
+                        },
+                    )
+                }
+            }
+        })
+    }
 }
@@ -1,5 +1,5 @@
 use proc_macro2::TokenStream;
-use quote::{format_ident, quote};
+use quote::{format_ident, quote, quote_spanned};
 use syn::Token;
 
 use super::property::*;
@@ -49,6 +49,27 @@ pub fn has_method(items: &[syn::ImplItem], method: &str) -> (bool, syn::Ident) {
     )
 }
 
+// without `type = ..`, `FooPrivate` or `FooImp` implement the wrapper `Foo`
+pub fn type_name(object_type: Option<&syn::Type>, self_ty: &syn::Type) -> syn::Ident {
+    let ty = object_type.unwrap_or(self_ty);
+    let ident = match ty {
+        syn::Type::Path(p) => p.path.segments.last().map(|s| s.ident.clone()),
+        _ => None,
+    }
+    .unwrap_or_else(|| format_ident!("Object"));
+    if object_type.is_some() {
+        return ident;
+    }
+    let name = ident.to_string();
+    match name
+        .strip_suffix("Private")
+        .or_else(|| name.strip_suffix("Imp"))
+    {
+        Some(stripped) if !stripped.is_empty() => syn::Ident::new(stripped, ident.span()),
+        _ => ident,
+    }
+}
+
 #[inline]
 pub fn constrain<F, T>(f: F) -> F
 where
@@ -64,6 +85,18 @@ pub fn make_stmt(tokens: TokenStream) -> TokenStream {
 
 mod keywords {
     syn::custom_keyword!(pod);
+    syn::custom_keyword!(batch);
+    syn::custom_keyword!(snapshot);
+    syn::custom_keyword!(serde);
+    syn::custom_keyword!(variant_dict);
+    syn::custom_keyword!(keyfile);
+    syn::custom_keyword!(debug);
+    syn::custom_keyword!(props_eq);
+    syn::custom_keyword!(duplicate);
+    syn::custom_keyword!(history);
+    syn::custom_keyword!(transaction);
+    syn::custom_keyword!(seal);
+    syn::custom_keyword!(sync);
 }
 
 pub enum ClassInheritance {
@@ -75,6 +108,19 @@ pub struct Args {
     pub type_: Option<syn::Type>,
     pub inheritance: ClassInheritance,
     pub pod: bool,
+    pub batch: bool,
+    pub snapshot: bool,
+    pub snapshot_derives: Vec<syn::Path>,
+    pub serde: bool,
+    pub variant_dict: bool,
+    pub keyfile: bool,
+    pub debug: bool,
+    pub props_eq: bool,
+    pub duplicate: bool,
+    pub history: bool,
+    pub transaction: bool,
+    pub seal: bool,
+    pub sync: bool,
 }
 
 impl Args {
@@ -82,6 +128,19 @@ impl Args {
         let mut type_ = None;
         let mut inheritance = None;
         let mut pod = false;
+        let mut batch = false;
+        let mut snapshot = false;
+        let mut snapshot_derives = vec![];
+        let mut serde = false;
+        let mut variant_dict = false;
+        let mut keyfile = false;
+        let mut debug = false;
+        let mut props_eq = false;
+        let mut duplicate = false;
+        let mut history = false;
+        let mut transaction = false;
+        let mut seal = false;
+        let mut sync = false;
 
         while !input.is_empty() {
             let lookahead = input.lookahead1();
@@ -112,6 +171,106 @@ impl Args {
                     return Err(syn::Error::new_spanned(kw, "Duplicate `pod` attribute"));
                 }
                 pod = true;
+            } else if !interface && lookahead.peek(keywords::snapshot) {
+                let kw = input.parse::<keywords::snapshot>()?;
+                if snapshot {
+                    return Err(syn::Error::new_spanned(
+                        kw,
+                        "Duplicate `snapshot` attribute",
+                    ));
+                }
+                snapshot = true;
+                if input.peek(syn::token::Paren) {
+                    let content;
+                    syn::parenthesized!(content in input);
+                    let derives =
+                        content.parse_terminated::<_, Token![,]>(syn::Path::parse_mod_style)?;
+                    snapshot_derives.extend(derives);
+                }
+            } else if !interface && lookahead.peek(keywords::serde) {
+                let kw = input.parse::<keywords::serde>()?;
+                if serde {
+                    return Err(syn::Error::new_spanned(kw, "Duplicate `serde` attribute"));
+                }
+                if !cfg!(feature = "serde") {
+                    return Err(syn::Error::new_spanned(
+                        kw,
+                        "`serde` requires the `serde` feature of gobject-impl",
+                    ));
+                }
+                serde = true;
+            } else if !interface && lookahead.peek(keywords::variant_dict) {
+                let kw = input.parse::<keywords::variant_dict>()?;
+                if variant_dict {
+                    return Err(syn::Error::new_spanned(
+                        kw,
+                        "Duplicate `variant_dict` attribute",
+                    ));
+                }
+                variant_dict = true;
+            } else if !interface && lookahead.peek(keywords::keyfile) {
+                let kw = input.parse::<keywords::keyfile>()?;
+                if keyfile {
+                    return Err(syn::Error::new_spanned(kw, "Duplicate `keyfile` attribute"));
+                }
+                keyfile = true;
+            } else if !interface && lookahead.peek(keywords::debug) {
+                let kw = input.parse::<keywords::debug>()?;
+                if debug {
+                    return Err(syn::Error::new_spanned(kw, "Duplicate `debug` attribute"));
+                }
+                debug = true;
+            } else if !interface && lookahead.peek(keywords::props_eq) {
+                let kw = input.parse::<keywords::props_eq>()?;
+                if props_eq {
+                    return Err(syn::Error::new_spanned(
+                        kw,
+                        "Duplicate `props_eq` attribute",
+                    ));
+                }
+                props_eq = true;
+            } else if !interface && lookahead.peek(keywords::duplicate) {
+                let kw = input.parse::<keywords::duplicate>()?;
+                if duplicate {
+                    return Err(syn::Error::new_spanned(
+                        kw,
+                        "Duplicate `duplicate` attribute",
+                    ));
+                }
+                duplicate = true;
+            } else if !interface && lookahead.peek(keywords::history) {
+                let kw = input.parse::<keywords::history>()?;
+                if history {
+                    return Err(syn::Error::new_spanned(kw, "Duplicate `history` attribute"));
+                }
+                history = true;
+            } else if !interface && lookahead.peek(keywords::transaction) {
+                let kw = input.parse::<keywords::transaction>()?;
+                if transaction {
+                    return Err(syn::Error::new_spanned(
+                        kw,
+                        "Duplicate `transaction` attribute",
+                    ));
+                }
+                transaction = true;
+            } else if !interface && lookahead.peek(keywords::seal) {
+                let kw = input.parse::<keywords::seal>()?;
+                if seal {
+                    return Err(syn::Error::new_spanned(kw, "Duplicate `seal` attribute"));
+                }
+                seal = true;
+            } else if lookahead.peek(keywords::batch) {
+                let kw = input.parse::<keywords::batch>()?;
+                if batch {
+                    return Err(syn::Error::new_spanned(kw, "Duplicate `batch` attribute"));
+                }
+                batch = true;
+            } else if lookahead.peek(keywords::sync) {
+                let kw = input.parse::<keywords::sync>()?;
+                if sync {
+                    return Err(syn::Error::new_spanned(kw, "Duplicate `sync` attribute"));
+                }
+                sync = true;
             } else if !interface && lookahead.peek(Token![final]) {
                 let kw = input.parse::<Token![final]>()?;
                 if inheritance.is_some() {
@@ -132,6 +291,12 @@ impl Args {
             if interface {
                 return Err(syn::Error::new(input.span(), "`type` attribute required"));
             }
+            if serde {
+                return Err(syn::Error::new(
+                    input.span(),
+                    "`type` attribute required with `serde`",
+                ));
+            }
             if matches!(inheritance, Some(ClassInheritance::Final)) {
                 return Err(syn::Error::new(
                     input.span(),
@@ -151,6 +316,19 @@ impl Args {
             type_,
             inheritance,
             pod,
+            batch,
+            snapshot,
+            snapshot_derives,
+            serde,
+            variant_dict,
+            keyfile,
+            debug,
+            props_eq,
+            duplicate,
+            history,
+            transaction,
+            seal,
+            sync,
         })
     }
 }
@@ -234,8 +412,7 @@ pub struct Output {
 impl Output {
     pub fn new(
         definition: &mut ObjectDefinition,
-        object_type: Option<&syn::Type>,
-        inheritance: &ClassInheritance,
+        args: &Args,
         signals_path: &TokenStream,
         properties_path: &TokenStream,
         go: &syn::Ident,
@@ -247,6 +424,8 @@ impl Output {
             ..
         } = definition;
         let glib = quote! { #go::glib };
+        let object_type = args.type_.as_ref();
+        let inheritance = &args.inheritance;
 
         let mut private_impl_methods = vec![];
         let mut prototypes = vec![];
@@ -279,6 +458,8 @@ impl Output {
             }
         }
 
+        let type_name = type_name(object_type, &item.self_ty);
+        let mut signal_types = vec![];
         for (index, signal) in signals.iter().enumerate() {
             prototypes.push(make_stmt(signal.signal_prototype(&glib)));
             methods.push(signal.signal_definition(index, signals_path, &glib));
@@ -286,9 +467,99 @@ impl Output {
                 prototypes.push(make_stmt(signal.emit_prototype(&glib)));
                 methods.push(signal.emit_definition(index, signals_path, &glib));
             }
+            prototypes.push(make_stmt(signal.stop_emission_prototype(false, &glib)));
+            methods.push(signal.stop_emission_definition(index, false, signals_path, &glib));
+            if signal.flags.contains(SignalFlags::DETAILED) {
+                prototypes.push(make_stmt(signal.stop_emission_prototype(true, &glib)));
+                methods.push(signal.stop_emission_definition(index, true, signals_path, &glib));
+            }
+            if signal.connect || signal.connect_after || signal.connect_async {
+                signal_types.push(signal.handler_id_definition(&type_name, &glib));
+                prototypes.push(make_stmt(signal.disconnect_prototype(&type_name)));
+                methods.push(signal.disconnect_definition(&type_name, &glib));
+                prototypes.push(make_stmt(signal.block_prototype(&type_name, go)));
+                methods.push(signal.block_definition(&type_name, go));
+            }
             if signal.connect {
-                prototypes.push(make_stmt(signal.connect_prototype(&glib)));
-                methods.push(signal.connect_definition(index, signals_path, &glib));
+                prototypes.push(make_stmt(
+                    signal.connect_prototype(false, false, args.sync, &type_name, &glib),
+                ));
+                methods.push(signal.connect_definition(
+                    index,
+                    false,
+                    args.sync,
+                    &type_name,
+                    signals_path,
+                    &glib,
+                ));
+                prototypes.push(make_stmt(
+                    signal.connect_prototype(false, true, args.sync, &type_name, &glib),
+                ));
+                methods.push(signal.typed_definition(false, false, args.sync, &type_name, &glib));
+                prototypes.push(make_stmt(signal.scoped_prototype(args.sync, go)));
+                methods.push(signal.scoped_definition(args.sync, go));
+                prototypes.push(make_stmt(
+                    signal.weak_prototype(false, args.sync, &type_name, &glib),
+                ));
+                methods.push(signal.weak_definition(
+                    index,
+                    args.sync,
+                    &type_name,
+                    signals_path,
+                    &glib,
+                ));
+                prototypes.push(make_stmt(
+                    signal.weak_prototype(true, args.sync, &type_name, &glib),
+                ));
+                methods.push(signal.typed_definition(false, true, args.sync, &type_name, &glib));
+            }
+            if let Some(future) = signal.future_prototype(go).filter(|_| signal.future) {
+                prototypes.push(make_stmt(future));
+                methods.push(
+                    signal
+                        .future_definition(index, args.sync, signals_path, go)
+                        .expect("no future definition"),
+                );
+            }
+            if let Some(stream) = signal
+                .stream_prototype(go)
+                .filter(|_| cfg!(feature = "futures") && signal.connect)
+            {
+                prototypes.push(make_stmt(stream));
+                methods.push(
+                    signal
+                        .stream_definition(index, args.sync, signals_path, go)
+                        .expect("no stream definition"),
+                );
+            }
+            if signal.connect_async {
+                prototypes.push(make_stmt(signal.async_prototype(&type_name, &glib)));
+                methods.push(signal.async_definition(index, &type_name, signals_path, go));
+            }
+            if let Some(hook) = signal.emission_hook_prototype(go) {
+                prototypes.push(make_stmt(hook));
+                methods.push(
+                    signal
+                        .emission_hook_definition(index, signals_path, go)
+                        .expect("no emission hook definition"),
+                );
+            }
+            if signal.connect_after {
+                prototypes.push(make_stmt(
+                    signal.connect_prototype(true, false, args.sync, &type_name, &glib),
+                ));
+                methods.push(signal.connect_definition(
+                    index,
+                    true,
+                    args.sync,
+                    &type_name,
+                    signals_path,
+                    &glib,
+                ));
+                prototypes.push(make_stmt(
+                    signal.connect_prototype(true, true, args.sync, &type_name, &glib),
+                ));
+                methods.push(signal.typed_definition(true, false, args.sync, &type_name, &glib));
             }
 
             if let Some(method) = signal.handler_definition() {
@@ -327,10 +598,48 @@ impl Output {
             let self_ty = &item.self_ty;
             quote! { <#self_ty as #glib::subclass::types::ObjectSubclass>::Type }
         };
+        let (batch_recv, setter_recv) = match inheritance {
+            ClassInheritance::Final => (quote! { #self_ty }, quote! { #self_ty }),
+            ClassInheritance::Abstract(trait_name) => {
+                (quote! { O as #trait_name }, quote! { Self as #trait_name })
+            }
+        };
+        let batch_name = format_ident!("{}Batch", type_name);
+        let mut batch_setters = vec![];
+        let transaction_name = format_ident!("{}Transaction", type_name);
+        let mut transaction_setters = vec![];
+        let state_name = format_ident!("{}State", type_name);
+        let mut state_fields = vec![];
+        let mut state_reads = vec![];
+        let mut state_writes = vec![];
+        let mut serde_pspecs = vec![];
+        let mut variant_pspecs = vec![];
+        let mut keyfile_pspecs = vec![];
+        let mut debug_pspecs = vec![];
+        let mut eq_pspecs = vec![];
+        let mut deep_pspecs = vec![];
         for (index, prop) in properties.iter().enumerate() {
             if prop.skip {
                 continue;
             }
+            if args.serde {
+                serde_pspecs.push(quote_spanned! { prop.span => &#properties_path()[#index] });
+            }
+            if args.variant_dict {
+                variant_pspecs.push(quote_spanned! { prop.span => &#properties_path()[#index] });
+            }
+            if args.debug && prop.is_debugged() {
+                debug_pspecs.push(quote_spanned! { prop.span => &#properties_path()[#index] });
+            }
+            if args.props_eq {
+                eq_pspecs.push(quote_spanned! { prop.span => &#properties_path()[#index] });
+            }
+            if args.duplicate && matches!(prop.special_type, PropertyType::Object(_)) {
+                deep_pspecs.push(quote_spanned! { prop.span => &#properties_path()[#index] });
+            }
+            if args.keyfile && prop.is_persisted() {
+                keyfile_pspecs.push(quote_spanned! { prop.span => &#properties_path()[#index] });
+            }
             if let Some(pspec) = prop.pspec_prototype(&glib) {
                 prototypes.push(make_stmt(pspec));
                 methods.push(
@@ -345,17 +654,47 @@ impl Output {
                         .expect("no notify definition"),
                 );
             }
-            if let Some(connect_notify) = prop.connect_prototype(&glib) {
+            if let Some(connect_notify) = prop.connect_prototype(args.sync, &glib) {
                 prototypes.push(make_stmt(connect_notify));
                 methods.push(
-                    prop.connect_definition(&glib)
+                    prop.connect_definition(args.sync, &glib)
+                        .expect("no connect notify definition"),
+                );
+            }
+            if let Some(stream) = prop
+                .stream_prototype(go)
+                .filter(|_| cfg!(feature = "futures"))
+            {
+                prototypes.push(make_stmt(stream));
+                methods.push(
+                    prop.stream_definition(args.sync, go)
+                        .expect("no stream definition"),
+                );
+            }
+            if let Some(connect_weak) = prop.connect_weak_prototype(args.sync, &glib) {
+                prototypes.push(make_stmt(connect_weak));
+                methods.push(
+                    prop.connect_weak_definition(args.sync, &glib)
                         .expect("no connect notify definition"),
                 );
             }
+            if args.snapshot && prop.get.is_allowed() && !prop.is_inherited() {
+                let field = prop.getter_name();
+                let ty = prop.inner_type(go);
+                let read = prop.read_expr(index, &self_ty, properties_path, go);
+                state_fields.push(quote_spanned! { prop.span => pub #field: #ty });
+                state_reads.push(quote_spanned! { prop.span => #field: #read });
+                if prop.setter_prototype(go).is_some() {
+                    let setter = prop.setter_name();
+                    state_writes.push(quote_spanned! { prop.span =>
+                        <#setter_recv>::#setter(self, ::core::clone::Clone::clone(&state.#field));
+                    });
+                }
+            }
             if let Some(getter) = prop.getter_prototype(go) {
                 prototypes.push(make_stmt(getter));
                 methods.push(
-                    prop.getter_definition(&self_ty, go)
+                    prop.getter_definition(index, &self_ty, properties_path, go)
                         .expect("no getter definition"),
                 );
             }
@@ -369,9 +708,282 @@ impl Output {
             if let Some(setter) = prop.setter_prototype(go) {
                 prototypes.push(make_stmt(setter));
                 methods.push(
-                    prop.setter_definition(index, &self_ty, properties_path, go)
-                        .expect("no setter definition"),
+                    prop.setter_definition(
+                        index,
+                        &self_ty,
+                        properties_path,
+                        args.history || args.seal,
+                        go,
+                    )
+                    .expect("no setter definition"),
                 );
+                if args.batch {
+                    batch_setters.push(prop.batch_setter_definition(&batch_recv, go));
+                }
+            }
+            if args.transaction {
+                transaction_setters.extend(prop.transaction_setter_definition(
+                    index,
+                    &self_ty,
+                    properties_path,
+                    args.seal,
+                    go,
+                ));
+            }
+        }
+
+        let mut batch_types = None;
+        if !batch_setters.is_empty() {
+            prototypes.push(make_stmt(quote! {
+                fn update<F: FnOnce(&#batch_name<'_, Self>)>(&self, f: F) where Self: Sized
+            }));
+            methods.push(quote! {
+                fn update<F: FnOnce(&#batch_name<'_, Self>)>(&self, f: F) where Self: Sized {
+                    let _guard = <Self as #glib::object::ObjectExt>::freeze_notify(self);
+                    f(&#batch_name(self));
+                }
+            });
+            let batch_impl = match inheritance {
+                ClassInheritance::Final => {
+                    let object_type = object_type.expect("no object_type");
+                    let mut generics = item.generics.clone();
+                    generics
+                        .params
+                        .insert(0, syn::parse2(quote! { 'a }).unwrap());
+                    let (impl_generics, _, where_clause) = generics.split_for_impl();
+                    let (_, ty_generics, _) = item.generics.split_for_impl();
+                    quote! {
+                        impl #impl_generics #batch_name<'a, #object_type #ty_generics> #where_clause
+                    }
+                }
+                ClassInheritance::Abstract(trait_name) => {
+                    quote! { impl<'a, O: #trait_name> #batch_name<'a, O> }
+                }
+            };
+            batch_types = Some(quote! {
+                pub struct #batch_name<'a, O>(&'a O);
+                #[allow(dead_code)]
+                #batch_impl {
+                    #(#batch_setters)*
+                }
+            });
+        }
+
+        if args.seal {
+            prototypes.push(make_stmt(quote! { fn seal(&self) }));
+            methods.push(quote! {
+                fn seal(&self) {
+                    #go::seal_object(self);
+                }
+            });
+            prototypes.push(make_stmt(quote! { fn is_sealed(&self) -> bool }));
+            methods.push(quote! {
+                fn is_sealed(&self) -> bool {
+                    #go::is_object_sealed(self)
+                }
+            });
+        }
+
+        let mut transaction_types = None;
+        if args.transaction {
+            let transaction = quote! {
+                fn transaction<R, E, F>(&self, f: F) -> ::core::result::Result<R, E>
+                where
+                    Self: Sized,
+                    F: FnOnce(&#transaction_name<'_, Self>) -> ::core::result::Result<R, E>
+            };
+            prototypes.push(make_stmt(transaction.clone()));
+            methods.push(quote! {
+                #transaction {
+                    let tx = #transaction_name(self, ::core::default::Default::default());
+                    let res = f(&tx);
+                    if res.is_ok() {
+                        tx.1.commit(self);
+                    } else {
+                        tx.1.rollback();
+                    }
+                    res
+                }
+            });
+            let transaction_impl = match inheritance {
+                ClassInheritance::Final => quote! { impl<'a> #transaction_name<'a, #self_ty> },
+                ClassInheritance::Abstract(_) => {
+                    quote! { impl<'a, O: #glib::IsA<#self_ty>> #transaction_name<'a, O> }
+                }
+            };
+            transaction_types = Some(quote! {
+                pub struct #transaction_name<'a, O>(&'a O, #go::TransactionLog<'a>);
+                #[allow(dead_code)]
+                #transaction_impl {
+                    #(#transaction_setters)*
+                }
+            });
+        }
+
+        let mut state_type = None;
+        if args.snapshot {
+            let snapshot_derives = &args.snapshot_derives;
+            prototypes.push(make_stmt(quote! { fn snapshot(&self) -> #state_name }));
+            methods.push(quote! {
+                fn snapshot(&self) -> #state_name {
+                    #state_name {
+                        #(#state_reads),*
+                    }
+                }
+            });
+            prototypes.push(make_stmt(quote! { fn apply(&self, state: &#state_name) }));
+            methods.push(quote! {
+                fn apply(&self, state: &#state_name) {
+                    let _guard = <Self as #glib::object::ObjectExt>::freeze_notify(self);
+                    #(#state_writes)*
+                }
+            });
+            state_type = Some(quote! {
+                #[derive(Clone, #(#snapshot_derives),*)]
+                pub struct #state_name {
+                    #(#state_fields),*
+                }
+            });
+        }
+
+        if args.variant_dict {
+            prototypes.push(make_stmt(quote! {
+                fn to_variant_dict(&self) -> #glib::Variant
+            }));
+            methods.push(quote! {
+                fn to_variant_dict(&self) -> #glib::Variant {
+                    #go::object_to_variant_dict(self, &[#(#variant_pspecs),*])
+                }
+            });
+            let from_variant_dict = quote! {
+                fn from_variant_dict(
+                    dict: &#glib::Variant,
+                ) -> ::core::result::Result<Self, #go::PropertyError>
+                where
+                    Self: #glib::IsA<#glib::Object>
+            };
+            prototypes.push(make_stmt(from_variant_dict.clone()));
+            methods.push(quote! {
+                #from_variant_dict {
+                    let obj = #go::object_from_variant_dict(
+                        <Self as #glib::StaticType>::static_type(),
+                        &[#(#variant_pspecs),*],
+                        dict,
+                    )?;
+                    ::core::result::Result::Ok(#glib::Cast::downcast(obj).unwrap())
+                }
+            });
+        }
+
+        if args.keyfile {
+            let save_to_keyfile = quote! {
+                fn save_to_keyfile(&self, keyfile: &#glib::KeyFile, group: &str)
+            };
+            prototypes.push(make_stmt(save_to_keyfile.clone()));
+            methods.push(quote! {
+                #save_to_keyfile {
+                    #go::save_properties_to_keyfile(self, &[#(#keyfile_pspecs),*], keyfile, group);
+                }
+            });
+            let load_from_keyfile = quote! {
+                fn load_from_keyfile(
+                    &self,
+                    keyfile: &#glib::KeyFile,
+                    group: &str,
+                ) -> ::core::result::Result<(), #go::PropertyError>
+            };
+            prototypes.push(make_stmt(load_from_keyfile.clone()));
+            methods.push(quote! {
+                #load_from_keyfile {
+                    #go::load_properties_from_keyfile(self, &[#(#keyfile_pspecs),*], keyfile, group)
+                }
+            });
+        }
+
+        if args.debug {
+            prototypes.push(make_stmt(quote! {
+                fn debug_properties(&self) -> #go::PropertiesDebug<'_>
+            }));
+            methods.push(quote! {
+                fn debug_properties(&self) -> #go::PropertiesDebug<'_> {
+                    #go::PropertiesDebug::new(self, ::std::vec![#(#debug_pspecs),*])
+                }
+            });
+        }
+
+        if args.props_eq {
+            prototypes.push(make_stmt(quote! {
+                fn props_eq(&self, other: &Self) -> bool
+            }));
+            methods.push(quote! {
+                fn props_eq(&self, other: &Self) -> bool {
+                    #go::properties_eq(self, other, &[#(#eq_pspecs),*])
+                }
+            });
+            prototypes.push(make_stmt(quote! {
+                fn props_hash<H: ::std::hash::Hasher>(&self, state: &mut H)
+            }));
+            methods.push(quote! {
+                fn props_hash<H: ::std::hash::Hasher>(&self, state: &mut H) {
+                    #go::hash_properties(self, &[#(#eq_pspecs),*], state);
+                }
+            });
+        }
+
+        if args.duplicate {
+            let duplicate = quote! {
+                fn duplicate(&self) -> Self
+                where
+                    Self: #glib::IsA<#glib::Object>
+            };
+            prototypes.push(make_stmt(duplicate.clone()));
+            methods.push(quote! {
+                #duplicate {
+                    #go::duplicate_object(self, &[])
+                }
+            });
+            let duplicate_deep = quote! {
+                fn duplicate_deep(&self) -> Self
+                where
+                    Self: #glib::IsA<#glib::Object>
+            };
+            prototypes.push(make_stmt(duplicate_deep.clone()));
+            methods.push(quote! {
+                #duplicate_deep {
+                    #go::duplicate_object(self, &[#(#deep_pspecs),*])
+                }
+            });
+        }
+
+        if args.history {
+            for (name, func) in [
+                ("begin_group", "history_begin_group"),
+                ("end_group", "history_end_group"),
+                ("clear_history", "history_clear"),
+            ] {
+                let name = format_ident!("{}", name);
+                let func = format_ident!("{}", func);
+                prototypes.push(make_stmt(quote! { fn #name(&self) }));
+                methods.push(quote! {
+                    fn #name(&self) {
+                        #go::#func(self);
+                    }
+                });
+            }
+            for (name, func) in [
+                ("undo", "history_undo"),
+                ("redo", "history_redo"),
+                ("can_undo", "history_can_undo"),
+                ("can_redo", "history_can_redo"),
+            ] {
+                let name = format_ident!("{}", name);
+                let func = format_ident!("{}", func);
+                prototypes.push(make_stmt(quote! { fn #name(&self) -> bool }));
+                methods.push(quote! {
+                    fn #name(&self) -> bool {
+                        #go::#func(self)
+                    }
+                });
             }
         }
 
@@ -410,6 +1022,49 @@ impl Output {
             }
         };
 
+        // glib::wrapper! already implements Debug for the object type, so it goes on the impl struct
+        let debug_impl = args.debug.then(|| {
+            let self_ty = &item.self_ty;
+            let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+            quote! {
+                impl #impl_generics ::std::fmt::Debug for #self_ty #ty_generics #where_clause {
+                    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                        let obj = #glib::subclass::types::ObjectSubclassExt::instance(self);
+                        ::std::fmt::Debug::fmt(
+                            &#go::PropertiesDebug::new(&obj, ::std::vec![#(#debug_pspecs),*]),
+                            f,
+                        )
+                    }
+                }
+            }
+        });
+
+        let serde_impls = args.serde.then(|| {
+            let object_type = object_type.expect("no object_type");
+            quote! {
+                impl #go::serde::Serialize for #object_type {
+                    fn serialize<S: #go::serde::Serializer>(&self, serializer: S) -> ::core::result::Result<S::Ok, S::Error> {
+                        #go::serialize_properties(self, &[#(#serde_pspecs),*], serializer)
+                    }
+                }
+                impl<'de> #go::serde::Deserialize<'de> for #object_type {
+                    fn deserialize<D: #go::serde::Deserializer<'de>>(deserializer: D) -> ::core::result::Result<Self, D::Error> {
+                        #go::deserialize_object(deserializer, &[#(#serde_pspecs),*])
+                    }
+                }
+            }
+        });
+
+        let public_methods = quote! {
+            #public_methods
+            #(#signal_types)*
+            #batch_types
+            #transaction_types
+            #state_type
+            #debug_impl
+            #serde_impls
+        };
+
         Self {
             private_impl_methods,
             prop_set_impls,
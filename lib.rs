@@ -1,9 +1,23 @@
-use glib::{translate::*, value::ValueType, ParamFlags, ParamSpec, Value};
+use glib::{translate::*, value::ValueType, ObjectExt, ObjectType, ParamFlags, ParamSpec, Value};
 use std::ops::DerefMut;
 
 pub use glib;
 pub use gobject_impl_macros::*;
 
+#[cfg(feature = "serde")]
+mod serialization;
+#[cfg(feature = "serde")]
+pub use serde;
+#[cfg(feature = "serde")]
+pub use serialization::*;
+
+#[cfg(feature = "futures")]
+pub use futures_core;
+#[cfg(feature = "futures")]
+mod stream;
+#[cfg(feature = "futures")]
+pub use stream::*;
+
 pub trait ParamSpecBuildable {
     type Builder;
 
@@ -300,25 +314,9 @@ impl ParamSpecVariantBuilder {
         self
     }
     pub fn build(self, name: &str, nick: &str, blurb: &str, flags: ParamFlags) -> ParamSpec {
-        let mut error = std::ptr::null_mut();
-        let default = self.default.map(|s| {
-            let end = &s[s.len()..];
-            unsafe {
-                let variant = glib::ffi::g_variant_parse(
-                    self.variant.to_glib_none().0,
-                    s.as_ptr() as *const _,
-                    end.as_ptr() as *const _,
-                    std::ptr::null_mut(),
-                    &mut error,
-                );
-                if error.is_null() {
-                    from_glib_none(variant)
-                } else {
-                    let err: glib::Error = from_glib_full(error);
-                    panic!("{}", err);
-                }
-            }
-        });
+        let default = self
+            .default
+            .map(|s| parse_variant(Some(self.variant), s).unwrap_or_else(|err| panic!("{}", err)));
         glib::ParamSpecVariant::new(name, nick, blurb, self.variant, default.as_ref(), flags)
     }
 }
@@ -333,6 +331,1604 @@ impl ParamSpecBuildable for glib::Variant {
     }
 }
 
+pub trait ObjectPspecExt: ObjectType {
+    fn set_property_from_pspec(&self, pspec: &ParamSpec, value: Value);
+    fn property_from_pspec(&self, pspec: &ParamSpec) -> Value;
+}
+
+thread_local! {
+    static SET_REJECTED: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+// called from `set_property` when it refuses a write, so no notify is sent for it
+pub fn reject_property_set() {
+    SET_REJECTED.with(|rejected| rejected.set(true));
+}
+
+// the class that installed the pspec, if that's the object's own class. pspecs of parent classes
+// and interfaces may be overridden, which GObject only resolves by name.
+unsafe fn owner_class(
+    obj: *mut glib::gobject_ffi::GObject,
+    pspec: *mut glib::gobject_ffi::GParamSpec,
+) -> Option<*mut glib::gobject_ffi::GObjectClass> {
+    let class = (*obj).g_type_instance.g_class as *mut glib::gobject_ffi::GObjectClass;
+    ((*class).g_type_class.g_type == (*pspec).owner_type).then_some(class)
+}
+
+// pspecs installed by the object's own class go straight to its vfuncs with the property's id,
+// doing what `g_object_set_property` and `g_object_get_property` do once they found the pspec
+impl<T: ObjectType> ObjectPspecExt for T {
+    fn set_property_from_pspec(&self, pspec: &ParamSpec, mut value: Value) {
+        let obj = unsafe { glib::Cast::unsafe_cast_ref::<glib::Object>(self) };
+        let flags = pspec.flags();
+        if !flags.contains(ParamFlags::WRITABLE) || flags.contains(ParamFlags::CONSTRUCT_ONLY) {
+            panic!(
+                "property '{}' of type '{}' is not writable",
+                pspec.name(),
+                obj.type_()
+            );
+        }
+        if !value.type_().is_a(pspec.value_type()) {
+            value = match value.get::<Option<glib::Object>>() {
+                Ok(Some(o)) if o.type_().is_a(pspec.value_type()) => {
+                    object_value(pspec.value_type(), &o)
+                }
+                _ => value
+                    .transform_with_type(pspec.value_type())
+                    .unwrap_or_else(|_| {
+                        panic!(
+                            "property '{}' of type '{}' can't be set from the given type (expected: '{}', got: '{}')",
+                            pspec.name(),
+                            obj.type_(),
+                            pspec.value_type(),
+                            value.type_()
+                        )
+                    }),
+            };
+        }
+        let _guard = obj.freeze_notify();
+        let obj_ptr: *mut glib::gobject_ffi::GObject = obj.to_glib_none().0;
+        let pspec_ptr: *mut glib::gobject_ffi::GParamSpec = pspec.to_glib_none().0;
+        let class = match unsafe { owner_class(obj_ptr, pspec_ptr) } {
+            Some(class) => class,
+            None => {
+                // GObject notifies even if `set_property` refuses the write
+                if check_unsealed(obj, pspec) {
+                    unsafe {
+                        glib::gobject_ffi::g_object_set_property(
+                            obj_ptr,
+                            (*pspec_ptr).name,
+                            value.to_glib_none().0,
+                        );
+                    }
+                }
+                return;
+            }
+        };
+        if !validate_value(pspec, &mut value) {
+            panic!(
+                "property '{}' of type '{}' can't be set from given value, it is invalid or out of range",
+                pspec.name(),
+                obj.type_(),
+            );
+        }
+        let outer = SET_REJECTED.with(|rejected| rejected.replace(false));
+        unsafe {
+            (*class).set_property.unwrap()(
+                obj_ptr,
+                (*pspec_ptr).param_id,
+                value.to_glib_none_mut().0,
+                pspec_ptr,
+            );
+        }
+        let rejected = SET_REJECTED.with(|rejected| rejected.replace(outer));
+        if !rejected
+            && !flags.contains(ParamFlags::EXPLICIT_NOTIFY)
+            && flags.contains(ParamFlags::READABLE)
+        {
+            obj.notify_by_pspec(pspec);
+        }
+    }
+    fn property_from_pspec(&self, pspec: &ParamSpec) -> Value {
+        let obj = unsafe { glib::Cast::unsafe_cast_ref::<glib::Object>(self) };
+        if !pspec.flags().contains(ParamFlags::READABLE) {
+            panic!(
+                "property '{}' of type '{}' is not readable",
+                pspec.name(),
+                obj.type_()
+            );
+        }
+        let mut value = Value::from_type(pspec.value_type());
+        let obj_ptr: *mut glib::gobject_ffi::GObject = obj.to_glib_none().0;
+        let pspec_ptr: *mut glib::gobject_ffi::GParamSpec = pspec.to_glib_none().0;
+        unsafe {
+            match owner_class(obj_ptr, pspec_ptr) {
+                Some(class) => (*class).get_property.unwrap()(
+                    obj_ptr,
+                    (*pspec_ptr).param_id,
+                    value.to_glib_none_mut().0,
+                    pspec_ptr,
+                ),
+                None => glib::gobject_ffi::g_object_get_property(
+                    obj_ptr,
+                    (*pspec_ptr).name,
+                    value.to_glib_none_mut().0,
+                ),
+            }
+        }
+        value
+    }
+}
+
+#[derive(Debug)]
+pub enum PropertyError {
+    UnknownProperty {
+        type_: glib::Type,
+        name: String,
+    },
+    TypeMismatch {
+        name: String,
+        expected: String,
+        found: String,
+    },
+    InvalidValue {
+        name: String,
+    },
+    Parse {
+        name: String,
+        message: String,
+    },
+    NotWritable {
+        name: String,
+    },
+    Sealed {
+        type_: glib::Type,
+    },
+    Construct(glib::BoolError),
+}
+
+impl std::fmt::Display for PropertyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::UnknownProperty { type_, name } => {
+                write!(f, "type '{}' has no property named '{}'", type_, name)
+            }
+            Self::TypeMismatch {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "property '{}' expected value of type '{}', found '{}'",
+                name, expected, found
+            ),
+            Self::InvalidValue { name } => {
+                write!(
+                    f,
+                    "value for property '{}' is invalid or out of range",
+                    name
+                )
+            }
+            Self::Parse { name, message } => {
+                write!(f, "can't parse value for property '{}': {}", name, message)
+            }
+            Self::NotWritable { name } => write!(f, "property '{}' is not writable", name),
+            Self::Sealed { type_ } => write!(f, "object of type '{}' is sealed", type_),
+            Self::Construct(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for PropertyError {}
+
+// `ToValue` on a `glib::Object` gives a value of type `GObject`, which can't be validated against a
+// pspec for a subclass
+pub(crate) fn object_value(type_: glib::Type, obj: &glib::Object) -> Value {
+    let mut value = Value::from_type(type_);
+    unsafe {
+        glib::gobject_ffi::g_value_set_object(value.to_glib_none_mut().0, obj.as_ptr() as *mut _);
+    }
+    value
+}
+
+pub(crate) fn validate_value(pspec: &ParamSpec, value: &mut Value) -> bool {
+    let changed: bool = unsafe {
+        from_glib(glib::gobject_ffi::g_param_value_validate(
+            pspec.to_glib_none().0,
+            value.to_glib_none_mut().0,
+        ))
+    };
+    !changed || pspec.flags().contains(ParamFlags::LAX_VALIDATION)
+}
+
+// construct properties have to be passed to the constructor, everything else is set afterwards
+pub(crate) fn new_object_with_values(
+    type_: glib::Type,
+    values: Vec<(&ParamSpec, Value)>,
+) -> Result<glib::Object, glib::BoolError> {
+    let (construct_values, values): (Vec<_>, Vec<_>) = values.into_iter().partition(|(p, _)| {
+        p.flags()
+            .intersects(ParamFlags::CONSTRUCT | ParamFlags::CONSTRUCT_ONLY)
+    });
+    let construct_values = construct_values
+        .iter()
+        .map(|(pspec, value)| (pspec.name(), value as &dyn glib::ToValue))
+        .collect::<Vec<_>>();
+    let obj = glib::Object::with_type(type_, &construct_values)?;
+    for (pspec, value) in values {
+        obj.set_property_from_pspec(pspec, value);
+    }
+    Ok(obj)
+}
+
+fn variant_type_of(pspec: &ParamSpec) -> Option<&glib::VariantTy> {
+    use glib::{StaticType, Type, VariantTy};
+
+    let type_ = pspec.value_type();
+    Some(match type_ {
+        Type::BOOL => VariantTy::BOOLEAN,
+        Type::I8 => VariantTy::INT16,
+        Type::U8 => VariantTy::BYTE,
+        Type::I32 => VariantTy::INT32,
+        Type::U32 => VariantTy::UINT32,
+        Type::I_LONG | Type::I64 => VariantTy::INT64,
+        Type::U_LONG | Type::U64 => VariantTy::UINT64,
+        Type::F32 | Type::F64 => VariantTy::DOUBLE,
+        Type::STRING => VariantTy::STRING,
+        Type::VARIANT => pspec
+            .downcast_ref::<glib::ParamSpecVariant>()
+            .and_then(|p| p.type_())
+            .unwrap_or(VariantTy::ANY),
+        _ if type_ == Type::static_type() || type_.is_a(Type::ENUM) => VariantTy::STRING,
+        _ if type_ == Vec::<String>::static_type() || type_.is_a(Type::FLAGS) => {
+            VariantTy::STRING_ARRAY
+        }
+        _ if type_.is_a(Type::OBJECT) => VariantTy::VARDICT,
+        _ => return None,
+    })
+}
+
+// returns `None` for NULL strings, variants and objects, objects that are already being converted,
+// and for types that have no GVariant equivalent like boxed types and pointers
+#[allow(clippy::unnecessary_cast)]
+pub fn value_to_variant(value: &Value) -> Option<glib::Variant> {
+    use glib::{StaticType, ToVariant, Type};
+
+    let type_ = value.type_();
+    Some(match type_ {
+        Type::BOOL => value.get::<bool>().unwrap().to_variant(),
+        Type::I8 => (value.get::<i8>().unwrap() as i16).to_variant(),
+        Type::U8 => value.get::<u8>().unwrap().to_variant(),
+        Type::I32 => value.get::<i32>().unwrap().to_variant(),
+        Type::U32 => value.get::<u32>().unwrap().to_variant(),
+        Type::I_LONG => (value.get::<glib::ILong>().unwrap().0 as i64).to_variant(),
+        Type::U_LONG => (value.get::<glib::ULong>().unwrap().0 as u64).to_variant(),
+        Type::I64 => value.get::<i64>().unwrap().to_variant(),
+        Type::U64 => value.get::<u64>().unwrap().to_variant(),
+        Type::F32 => (value.get::<f32>().unwrap() as f64).to_variant(),
+        Type::F64 => value.get::<f64>().unwrap().to_variant(),
+        Type::STRING => value.get::<Option<&str>>().unwrap()?.to_variant(),
+        Type::VARIANT => value.get::<Option<glib::Variant>>().unwrap()?,
+        _ if type_ == Type::static_type() => value.get::<Type>().unwrap().name().to_variant(),
+        _ if type_ == Vec::<String>::static_type() => {
+            value.get::<Vec<String>>().unwrap().to_variant()
+        }
+        _ if type_.is_a(Type::ENUM) => glib::EnumValue::from_value(value)?.1.nick().to_variant(),
+        _ if type_.is_a(Type::FLAGS) => glib::FlagsValue::from_value(value)?
+            .1
+            .iter()
+            .map(|f| f.nick())
+            .collect::<Vec<_>>()
+            .to_variant(),
+        _ if type_.is_a(Type::OBJECT) => {
+            let obj = value.get::<Option<glib::Object>>().unwrap()?;
+            if VARIANT_VISITING.with(|v| v.borrow().contains(&(obj.as_ptr() as usize))) {
+                return None;
+            }
+            let pspecs = obj.list_properties();
+            object_to_variant_dict(&obj, &pspecs.iter().collect::<Vec<_>>())
+        }
+        _ => return None,
+    })
+}
+
+// c_long is 32-bit on some platforms
+#[allow(clippy::useless_conversion)]
+pub fn value_from_variant(
+    pspec: &ParamSpec,
+    variant: &glib::Variant,
+) -> Result<Value, PropertyError> {
+    use glib::{StaticType, ToValue, Type};
+
+    let type_ = pspec.value_type();
+    let mismatch = |expected: &str| PropertyError::TypeMismatch {
+        name: pspec.name().to_owned(),
+        expected: expected.to_owned(),
+        found: variant.type_().as_str().to_owned(),
+    };
+    let variant_type = variant_type_of(pspec).ok_or_else(|| mismatch(type_.name()))?;
+    if !variant.type_().is_subtype_of(variant_type) {
+        return Err(mismatch(variant_type.as_str()));
+    }
+    let invalid = || PropertyError::InvalidValue {
+        name: pspec.name().to_owned(),
+    };
+    let mut value = match type_ {
+        Type::BOOL => variant.get::<bool>().unwrap().to_value(),
+        Type::I8 => i8::try_from(variant.get::<i16>().unwrap())
+            .map_err(|_| invalid())?
+            .to_value(),
+        Type::U8 => variant.get::<u8>().unwrap().to_value(),
+        Type::I32 => variant.get::<i32>().unwrap().to_value(),
+        Type::U32 => variant.get::<u32>().unwrap().to_value(),
+        Type::I_LONG => glib::ILong(
+            variant
+                .get::<i64>()
+                .unwrap()
+                .try_into()
+                .map_err(|_| invalid())?,
+        )
+        .to_value(),
+        Type::U_LONG => glib::ULong(
+            variant
+                .get::<u64>()
+                .unwrap()
+                .try_into()
+                .map_err(|_| invalid())?,
+        )
+        .to_value(),
+        Type::I64 => variant.get::<i64>().unwrap().to_value(),
+        Type::U64 => variant.get::<u64>().unwrap().to_value(),
+        Type::F32 => (variant.get::<f64>().unwrap() as f32).to_value(),
+        Type::F64 => variant.get::<f64>().unwrap().to_value(),
+        Type::STRING => variant.str().to_value(),
+        Type::VARIANT => variant.to_value(),
+        _ if type_ == Type::static_type() => Type::from_name(variant.str().unwrap())
+            .ok_or_else(invalid)?
+            .to_value(),
+        _ if type_ == Vec::<String>::static_type() => {
+            variant.get::<Vec<String>>().unwrap().to_value()
+        }
+        _ if type_.is_a(Type::ENUM) => glib::EnumClass::new(type_)
+            .and_then(|c| c.to_value_by_nick(variant.str().unwrap()))
+            .ok_or_else(invalid)?,
+        _ if type_.is_a(Type::FLAGS) => glib::FlagsClass::new(type_)
+            .and_then(|c| {
+                variant
+                    .get::<Vec<String>>()
+                    .unwrap()
+                    .iter()
+                    .fold(c.builder(), |b, n| b.set_by_nick(n))
+                    .build()
+            })
+            .ok_or_else(invalid)?,
+        _ if type_.is_a(Type::OBJECT) => {
+            let class = glib::object::ObjectClass::from_type(type_).ok_or_else(|| {
+                PropertyError::Construct(glib::bool_error!(
+                    "Can't retrieve class for type '{}'",
+                    type_
+                ))
+            })?;
+            let pspecs = class.list_properties();
+            let obj = object_from_variant_dict(type_, &pspecs.iter().collect::<Vec<_>>(), variant)?;
+            object_value(type_, &obj)
+        }
+        _ => unreachable!(),
+    };
+    if !validate_value(pspec, &mut value) {
+        return Err(invalid());
+    }
+    Ok(value)
+}
+
+thread_local! {
+    static VARIANT_VISITING: std::cell::RefCell<std::collections::HashSet<usize>> =
+        Default::default();
+}
+
+// objects that are already being converted further up are left out, so reference cycles end
+pub fn object_to_variant_dict<O: ObjectType>(obj: &O, pspecs: &[&ParamSpec]) -> glib::Variant {
+    struct Visit(usize, bool);
+    impl Drop for Visit {
+        fn drop(&mut self) {
+            if self.1 {
+                VARIANT_VISITING.with(|v| v.borrow_mut().remove(&self.0));
+            }
+        }
+    }
+
+    let key = obj.as_ptr() as usize;
+    let _visit = Visit(key, VARIANT_VISITING.with(|v| v.borrow_mut().insert(key)));
+    let dict = glib::VariantDict::new(None);
+    for pspec in pspecs {
+        if !pspec.flags().contains(ParamFlags::READABLE) {
+            continue;
+        }
+        if let Some(variant) = value_to_variant(&obj.property_from_pspec(pspec)) {
+            dict.insert_value(pspec.name(), &variant);
+        }
+    }
+    dict.end()
+}
+
+// keys for properties that are not writable are ignored so the output of
+// `object_to_variant_dict` can always be passed back in
+pub fn object_from_variant_dict(
+    type_: glib::Type,
+    pspecs: &[&ParamSpec],
+    dict: &glib::Variant,
+) -> Result<glib::Object, PropertyError> {
+    if !dict.is_type(glib::VariantTy::VARDICT) {
+        return Err(PropertyError::TypeMismatch {
+            name: type_.name().to_owned(),
+            expected: glib::VariantTy::VARDICT.as_str().to_owned(),
+            found: dict.type_().as_str().to_owned(),
+        });
+    }
+    let mut values = Vec::new();
+    for entry in dict.iter() {
+        let name = entry.child_value(0);
+        let name = name.str().unwrap();
+        let pspec = pspecs.iter().find(|p| p.name() == name).ok_or_else(|| {
+            PropertyError::UnknownProperty {
+                type_,
+                name: name.to_owned(),
+            }
+        })?;
+        if !pspec.flags().contains(ParamFlags::WRITABLE) {
+            continue;
+        }
+        let variant = entry.child_value(1).as_variant().unwrap();
+        values.push((*pspec, value_from_variant(pspec, &variant)?));
+    }
+    new_object_with_values(type_, values).map_err(PropertyError::Construct)
+}
+
+pub trait PropertyFromStrExt: ObjectType {
+    fn set_property_from_str(&self, name: &str, value: &str) -> Result<(), PropertyError>;
+}
+
+impl<T: ObjectType> PropertyFromStrExt for T {
+    fn set_property_from_str(&self, name: &str, value: &str) -> Result<(), PropertyError> {
+        let pspec = self
+            .find_property(name)
+            .ok_or_else(|| PropertyError::UnknownProperty {
+                type_: self.type_(),
+                name: name.to_owned(),
+            })?;
+        if !pspec.flags().contains(ParamFlags::WRITABLE)
+            || pspec.flags().contains(ParamFlags::CONSTRUCT_ONLY)
+        {
+            return Err(PropertyError::NotWritable {
+                name: pspec.name().to_owned(),
+            });
+        }
+        if is_object_sealed(self) {
+            return Err(PropertyError::Sealed {
+                type_: self.type_(),
+            });
+        }
+        let value = value_from_str(&pspec, value)?;
+        self.set_property_from_pspec(&pspec, value);
+        Ok(())
+    }
+}
+
+pub(crate) fn parse_variant(
+    type_: Option<&glib::VariantTy>,
+    text: &str,
+) -> Result<glib::Variant, glib::Error> {
+    let end = &text[text.len()..];
+    unsafe {
+        let mut error = std::ptr::null_mut();
+        let variant = glib::ffi::g_variant_parse(
+            type_.to_glib_none().0,
+            text.as_ptr() as *const _,
+            end.as_ptr() as *const _,
+            std::ptr::null_mut(),
+            &mut error,
+        );
+        if error.is_null() {
+            Ok(from_glib_full(variant))
+        } else {
+            Err(from_glib_full(error))
+        }
+    }
+}
+
+// enums accept nicks or full names, flags accept `a|b` with either
+pub fn value_from_str(pspec: &ParamSpec, text: &str) -> Result<Value, PropertyError> {
+    use glib::{StaticType, ToValue, Type};
+
+    fn parse<T: std::str::FromStr>(pspec: &ParamSpec, text: &str) -> Result<T, PropertyError>
+    where
+        T::Err: std::fmt::Display,
+    {
+        text.trim()
+            .parse::<T>()
+            .map_err(|err| PropertyError::Parse {
+                name: pspec.name().to_owned(),
+                message: err.to_string(),
+            })
+    }
+    let error = |message: String| PropertyError::Parse {
+        name: pspec.name().to_owned(),
+        message,
+    };
+
+    let type_ = pspec.value_type();
+    let mut value = match type_ {
+        Type::BOOL => match text.trim() {
+            "1" => true,
+            "0" => false,
+            text => parse::<bool>(pspec, text)?,
+        }
+        .to_value(),
+        Type::I8 => parse::<i8>(pspec, text)?.to_value(),
+        Type::U8 => parse::<u8>(pspec, text)?.to_value(),
+        Type::I32 => parse::<i32>(pspec, text)?.to_value(),
+        Type::U32 => parse::<u32>(pspec, text)?.to_value(),
+        Type::I_LONG => glib::ILong(parse(pspec, text)?).to_value(),
+        Type::U_LONG => glib::ULong(parse(pspec, text)?).to_value(),
+        Type::I64 => parse::<i64>(pspec, text)?.to_value(),
+        Type::U64 => parse::<u64>(pspec, text)?.to_value(),
+        Type::F32 => parse::<f32>(pspec, text)?.to_value(),
+        Type::F64 => parse::<f64>(pspec, text)?.to_value(),
+        Type::STRING => text.to_value(),
+        Type::VARIANT => {
+            let variant_type = pspec
+                .downcast_ref::<glib::ParamSpecVariant>()
+                .and_then(|p| p.type_());
+            parse_variant(variant_type, text)
+                .map_err(|err| error(err.message().to_owned()))?
+                .to_value()
+        }
+        _ if type_ == Type::static_type() => Type::from_name(text.trim())
+            .ok_or_else(|| error(format!("unknown type '{}'", text.trim())))?
+            .to_value(),
+        _ if type_.is_a(Type::ENUM) => {
+            let class = glib::EnumClass::new(type_).unwrap();
+            let text = text.trim();
+            class
+                .to_value_by_nick(text)
+                .or_else(|| class.to_value_by_name(text))
+                .ok_or_else(|| error(format!("'{}' is not a value of enum '{}'", text, type_)))?
+        }
+        _ if type_.is_a(Type::FLAGS) => {
+            let class = glib::FlagsClass::new(type_).unwrap();
+            let mut builder = class.builder();
+            for name in text.split('|').map(|n| n.trim()).filter(|n| !n.is_empty()) {
+                builder = if class.value_by_nick(name).is_some() {
+                    builder.set_by_nick(name)
+                } else if class.value_by_name(name).is_some() {
+                    builder.set_by_name(name)
+                } else {
+                    return Err(error(format!(
+                        "'{}' is not a value of flags '{}'",
+                        name, type_
+                    )));
+                };
+            }
+            builder.build().unwrap()
+        }
+        _ => {
+            return Err(error(format!(
+                "values of type '{}' can't be parsed from a string",
+                type_
+            )))
+        }
+    };
+    if !validate_value(pspec, &mut value) {
+        return Err(PropertyError::InvalidValue {
+            name: pspec.name().to_owned(),
+        });
+    }
+    Ok(value)
+}
+
+pub type PropertyValues = Vec<(ParamSpec, Value)>;
+
+// a property can be passed as `--name=value` or `--name value`, boolean properties can also be
+// passed as just `--name`. arguments after `--` and ones not starting with `--` are returned as is.
+pub fn parse_property_args<I, S>(
+    type_: glib::Type,
+    args: I,
+) -> Result<(PropertyValues, Vec<String>), PropertyError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let class = glib::object::ObjectClass::from_type(type_).ok_or_else(|| {
+        PropertyError::Construct(glib::bool_error!(
+            "Can't retrieve class for type '{}'",
+            type_
+        ))
+    })?;
+    let mut values = PropertyValues::new();
+    let mut rest = Vec::new();
+    let mut args = args.into_iter().map(|a| a.as_ref().to_owned());
+    while let Some(arg) = args.next() {
+        let option = match arg.strip_prefix("--") {
+            Some("") => {
+                rest.extend(args);
+                break;
+            }
+            Some(option) => option,
+            None => {
+                rest.push(arg);
+                continue;
+            }
+        };
+        let (name, text) = match option.split_once('=') {
+            Some((name, text)) => (name, Some(text.to_owned())),
+            None => (option, None),
+        };
+        let pspec = class
+            .find_property(name)
+            .ok_or_else(|| PropertyError::UnknownProperty {
+                type_,
+                name: name.to_owned(),
+            })?;
+        if !pspec.flags().contains(ParamFlags::WRITABLE) {
+            return Err(PropertyError::NotWritable {
+                name: pspec.name().to_owned(),
+            });
+        }
+        let text = match text {
+            Some(text) => text,
+            None if pspec.value_type() == glib::Type::BOOL => "true".to_owned(),
+            None => args.next().ok_or_else(|| PropertyError::Parse {
+                name: pspec.name().to_owned(),
+                message: format!("missing value for `--{}`", name),
+            })?,
+        };
+        let value = value_from_str(&pspec, &text)?;
+        values.retain(|(p, _)| p != &pspec);
+        values.push((pspec, value));
+    }
+    Ok((values, rest))
+}
+
+pub fn object_from_args<T, I, S>(args: I) -> Result<(T, Vec<String>), PropertyError>
+where
+    T: glib::IsA<glib::Object>,
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let (values, rest) = parse_property_args(T::static_type(), args)?;
+    let values = values.iter().map(|(p, v)| (p, v.clone())).collect();
+    let obj = new_object_with_values(T::static_type(), values).map_err(PropertyError::Construct)?;
+    Ok((glib::Cast::downcast(obj).unwrap(), rest))
+}
+
+fn value_to_string(value: &Value) -> Option<String> {
+    use glib::{StaticType, Type};
+
+    let type_ = value.type_();
+    Some(match type_ {
+        Type::STRING => value.get::<Option<String>>().unwrap()?,
+        Type::VARIANT => value
+            .get::<Option<glib::Variant>>()
+            .unwrap()?
+            .print(false)
+            .into(),
+        _ if type_ == Type::static_type() => value.get::<Type>().unwrap().name().to_owned(),
+        _ if type_.is_a(Type::ENUM) => glib::EnumValue::from_value(value)?.1.nick().to_owned(),
+        _ if type_.is_a(Type::FLAGS) => glib::FlagsValue::from_value(value)?
+            .1
+            .iter()
+            .map(|v| v.nick())
+            .collect::<Vec<_>>()
+            .join("|"),
+        _ => value
+            .transform::<String>()
+            .ok()?
+            .get::<Option<String>>()
+            .ok()??,
+    })
+}
+
+// compares with `g_param_values_cmp`, so floats within the pspec's epsilon are equal, string
+// vectors are compared by content
+pub fn properties_eq<O: ObjectType>(a: &O, b: &O, pspecs: &[&ParamSpec]) -> bool {
+    pspecs
+        .iter()
+        .filter(|p| p.flags().contains(ParamFlags::READABLE))
+        .all(|pspec| {
+            values_eq(
+                pspec,
+                &a.property_from_pspec(pspec),
+                &b.property_from_pspec(pspec),
+            )
+        })
+}
+
+fn values_eq(pspec: &ParamSpec, a: &Value, b: &Value) -> bool {
+    use glib::StaticType;
+
+    // boxed values are compared by pointer in `g_param_values_cmp`, so compare string vectors
+    // by content instead
+    if pspec.value_type() == Vec::<String>::static_type() {
+        return a.get::<Vec<String>>().ok() == b.get::<Vec<String>>().ok();
+    }
+    unsafe {
+        glib::gobject_ffi::g_param_values_cmp(
+            pspec.to_glib_none().0,
+            a.to_glib_none().0,
+            b.to_glib_none().0,
+        ) == 0
+    }
+}
+
+// only hashes what `properties_eq` compares exactly, floats and variants contribute nothing so
+// that equal objects always hash the same
+pub fn hash_properties<O: ObjectType, H: std::hash::Hasher>(
+    obj: &O,
+    pspecs: &[&ParamSpec],
+    state: &mut H,
+) {
+    use glib::{StaticType, Type};
+    use std::hash::Hash;
+
+    for pspec in pspecs
+        .iter()
+        .filter(|p| p.flags().contains(ParamFlags::READABLE))
+    {
+        let value = obj.property_from_pspec(pspec);
+        let type_ = value.type_();
+        pspec.name().hash(state);
+        match type_ {
+            Type::BOOL => value.get::<bool>().unwrap().hash(state),
+            Type::I8 => value.get::<i8>().unwrap().hash(state),
+            Type::U8 => value.get::<u8>().unwrap().hash(state),
+            Type::I32 => value.get::<i32>().unwrap().hash(state),
+            Type::U32 => value.get::<u32>().unwrap().hash(state),
+            Type::I_LONG => value.get::<glib::ILong>().unwrap().0.hash(state),
+            Type::U_LONG => value.get::<glib::ULong>().unwrap().0.hash(state),
+            Type::I64 => value.get::<i64>().unwrap().hash(state),
+            Type::U64 => value.get::<u64>().unwrap().hash(state),
+            Type::STRING => value.get::<Option<&str>>().unwrap().hash(state),
+            _ if type_ == Vec::<String>::static_type() => {
+                value.get::<Vec<String>>().unwrap().hash(state)
+            }
+            _ if type_ == Type::static_type() => value.get::<Type>().unwrap().hash(state),
+            _ if type_.is_a(Type::ENUM) => glib::EnumValue::from_value(&value)
+                .map(|(_, v)| v.value())
+                .hash(state),
+            _ if type_.is_a(Type::FLAGS) => glib::FlagsValue::from_value(&value)
+                .map(|(_, v)| v.iter().fold(0, |acc, v| acc | v.value()))
+                .hash(state),
+            _ if type_.is_a(Type::OBJECT) => value
+                .get::<Option<glib::Object>>()
+                .unwrap()
+                .map(|o| o.as_ptr() as usize)
+                .hash(state),
+            _ => {}
+        }
+    }
+}
+
+// copies through a new instance of the runtime type, object properties matching `deep` are
+// replaced by shallow copies of their values, an object shared between properties is copied once
+pub fn duplicate_object<O: glib::IsA<glib::Object>>(obj: &O, deep: &[&ParamSpec]) -> O {
+    use glib::Cast;
+
+    let mut copies = std::collections::HashMap::new();
+    let obj = duplicate_with(
+        obj.upcast_ref(),
+        &|pspec| deep.contains(&pspec),
+        &mut copies,
+    );
+    obj.downcast().unwrap()
+}
+
+fn duplicate_with(
+    obj: &glib::Object,
+    deep: &dyn Fn(&ParamSpec) -> bool,
+    copies: &mut std::collections::HashMap<usize, glib::Object>,
+) -> glib::Object {
+    let pspecs = obj.list_properties();
+    let values = pspecs
+        .iter()
+        .filter(|p| {
+            p.flags()
+                .contains(ParamFlags::READABLE | ParamFlags::WRITABLE)
+        })
+        .map(|pspec| {
+            let mut value = obj.property_from_pspec(pspec);
+            if deep(pspec) {
+                if let Ok(Some(child)) = value.get::<Option<glib::Object>>() {
+                    let key = child.as_ptr() as usize;
+                    if !copies.contains_key(&key) {
+                        let copy = duplicate_with(&child, &|_| false, copies);
+                        copies.insert(key, copy);
+                    }
+                    value = object_value(pspec.value_type(), &copies[&key]);
+                }
+            }
+            (pspec, value)
+        })
+        .collect();
+    new_object_with_values(obj.type_(), values).unwrap_or_else(|err| {
+        panic!(
+            "Failed to duplicate object of type '{}': {}",
+            obj.type_(),
+            err
+        )
+    })
+}
+
+struct HistoryEntry {
+    pspec: ParamSpec,
+    old: Value,
+    new: Value,
+}
+
+#[derive(Default)]
+struct PropertyHistory {
+    undo: Vec<Vec<HistoryEntry>>,
+    redo: Vec<Vec<HistoryEntry>>,
+    group: Vec<HistoryEntry>,
+    depth: usize,
+    replaying: bool,
+    constructed: std::collections::HashSet<ParamSpec>,
+}
+
+fn property_history<O: ObjectType>(obj: &O) -> &std::cell::RefCell<PropertyHistory> {
+    let key = glib::Quark::from_str("gobject-impl-history");
+    unsafe {
+        if obj
+            .qdata::<std::cell::RefCell<PropertyHistory>>(key)
+            .is_none()
+        {
+            obj.set_qdata(key, std::cell::RefCell::new(PropertyHistory::default()));
+        }
+        obj.qdata::<std::cell::RefCell<PropertyHistory>>(key)
+            .unwrap()
+            .as_ref()
+    }
+}
+
+// construct-only properties can't be written again so they are never recorded, construct
+// properties are set exactly once while the object is constructed so their first set is skipped
+pub fn history_snapshot<O: ObjectType>(obj: &O, pspec: &ParamSpec) -> Option<Value> {
+    let flags = pspec.flags();
+    if !flags.contains(ParamFlags::READABLE) || flags.contains(ParamFlags::CONSTRUCT_ONLY) {
+        return None;
+    }
+    // the getter may run user code that touches the history, don't hold the borrow across it
+    {
+        let mut history = property_history(obj).borrow_mut();
+        let first_construct =
+            flags.contains(ParamFlags::CONSTRUCT) && history.constructed.insert(pspec.clone());
+        if first_construct || history.replaying {
+            return None;
+        }
+    }
+    Some(obj.property_from_pspec(pspec))
+}
+
+pub fn history_record<O: ObjectType>(obj: &O, pspec: &ParamSpec, old: Option<Value>) {
+    let old = match old {
+        Some(old) => old,
+        None => return,
+    };
+    let new = obj.property_from_pspec(pspec);
+    if values_eq(pspec, &old, &new) {
+        return;
+    }
+    let mut history = property_history(obj).borrow_mut();
+    history.redo.clear();
+    history.group.push(HistoryEntry {
+        pspec: pspec.clone(),
+        old,
+        new,
+    });
+    if history.depth == 0 {
+        let group = std::mem::take(&mut history.group);
+        history.undo.push(group);
+    }
+}
+
+pub fn history_begin_group<O: ObjectType>(obj: &O) {
+    property_history(obj).borrow_mut().depth += 1;
+}
+
+pub fn history_end_group<O: ObjectType>(obj: &O) {
+    let mut history = property_history(obj).borrow_mut();
+    assert!(
+        history.depth > 0,
+        "end_group() called without begin_group()"
+    );
+    history.depth -= 1;
+    if history.depth == 0 && !history.group.is_empty() {
+        let group = std::mem::take(&mut history.group);
+        history.undo.push(group);
+    }
+}
+
+fn history_replay<O: ObjectType>(obj: &O, undo: bool) -> bool {
+    let history = property_history(obj);
+    let group = {
+        let mut history = history.borrow_mut();
+        assert_eq!(
+            history.depth, 0,
+            "can't undo or redo inside a history group"
+        );
+        let stack = if undo {
+            &mut history.undo
+        } else {
+            &mut history.redo
+        };
+        match stack.pop() {
+            Some(group) => group,
+            None => return false,
+        }
+    };
+    history.borrow_mut().replaying = true;
+    {
+        let _guard = obj.freeze_notify();
+        if undo {
+            for entry in group.iter().rev() {
+                obj.set_property_from_pspec(&entry.pspec, entry.old.clone());
+            }
+        } else {
+            for entry in &group {
+                obj.set_property_from_pspec(&entry.pspec, entry.new.clone());
+            }
+        }
+    }
+    let mut history = history.borrow_mut();
+    history.replaying = false;
+    if undo {
+        history.redo.push(group);
+    } else {
+        history.undo.push(group);
+    }
+    true
+}
+
+pub fn history_undo<O: ObjectType>(obj: &O) -> bool {
+    history_replay(obj, true)
+}
+
+pub fn history_redo<O: ObjectType>(obj: &O) -> bool {
+    history_replay(obj, false)
+}
+
+pub fn history_can_undo<O: ObjectType>(obj: &O) -> bool {
+    !property_history(obj).borrow().undo.is_empty()
+}
+
+pub fn history_can_redo<O: ObjectType>(obj: &O) -> bool {
+    !property_history(obj).borrow().redo.is_empty()
+}
+
+pub fn history_clear<O: ObjectType>(obj: &O) {
+    let mut history = property_history(obj).borrow_mut();
+    history.undo.clear();
+    history.redo.clear();
+    history.group.clear();
+    history.depth = 0;
+}
+
+fn sealed_quark() -> glib::Quark {
+    glib::Quark::from_str("gobject-impl-sealed")
+}
+
+pub fn seal_object<O: ObjectType>(obj: &O) {
+    unsafe { obj.set_qdata(sealed_quark(), true) };
+}
+
+pub fn is_object_sealed<O: ObjectType>(obj: &O) -> bool {
+    unsafe { obj.qdata::<bool>(sealed_quark()).is_some() }
+}
+
+// emits a critical and returns false if the object is sealed
+pub fn check_unsealed<O: ObjectType>(obj: &O, pspec: &ParamSpec) -> bool {
+    if is_object_sealed(obj) {
+        glib::g_critical!(
+            "gobject-impl",
+            "Can't set property '{}' on sealed object of type '{}'",
+            pspec.name(),
+            obj.type_()
+        );
+        return false;
+    }
+    true
+}
+
+type RestoreFn<'a> = Box<dyn FnOnce() + 'a>;
+
+// writes made through a transaction go straight to storage, notifies are only sent on commit.
+// they bypass the setters, so they also aren't recorded in the undo history. a log that is
+// dropped without being committed, e.g. by a panic, rolls back
+#[derive(Default)]
+pub struct TransactionLog<'a> {
+    changes: std::cell::RefCell<Vec<(ParamSpec, RestoreFn<'a>)>>,
+}
+
+impl<'a> TransactionLog<'a> {
+    pub fn record<F: FnOnce() + 'a>(&self, pspec: &ParamSpec, restore: F) {
+        self.changes
+            .borrow_mut()
+            .push((pspec.clone(), Box::new(restore)));
+    }
+    pub fn commit<O: ObjectType>(self, obj: &O) {
+        let changes = self.changes.take();
+        let _guard = obj.freeze_notify();
+        for (pspec, _) in &changes {
+            obj.notify_by_pspec(pspec);
+        }
+    }
+    pub fn rollback(self) {}
+}
+
+impl<'a> Drop for TransactionLog<'a> {
+    fn drop(&mut self) {
+        for (_, restore) in self.changes.take().into_iter().rev() {
+            restore();
+        }
+    }
+}
+
+// disconnects on drop, unless the object has already been finalized
+pub struct SignalHandlerGuard {
+    obj: glib::WeakRef<glib::Object>,
+    id: Option<glib::SignalHandlerId>,
+}
+
+impl SignalHandlerGuard {
+    pub fn new<O: ObjectType>(obj: &O, id: glib::SignalHandlerId) -> Self {
+        use glib::Cast;
+
+        let obj = unsafe { obj.unsafe_cast_ref::<glib::Object>() };
+        Self {
+            obj: obj.downgrade(),
+            id: Some(id),
+        }
+    }
+}
+
+impl Drop for SignalHandlerGuard {
+    fn drop(&mut self) {
+        if let (Some(obj), Some(id)) = (self.obj.upgrade(), self.id.take()) {
+            obj.disconnect(id);
+        }
+    }
+}
+
+pub struct SignalBlockGuard<'a> {
+    obj: &'a glib::Object,
+    id: &'a glib::SignalHandlerId,
+}
+
+impl<'a> SignalBlockGuard<'a> {
+    pub fn new<O: ObjectType>(obj: &'a O, id: &'a glib::SignalHandlerId) -> Self {
+        use glib::Cast;
+
+        let obj = unsafe { obj.unsafe_cast_ref::<glib::Object>() };
+        obj.block_signal(id);
+        Self { obj, id }
+    }
+}
+
+impl<'a> Drop for SignalBlockGuard<'a> {
+    fn drop(&mut self) {
+        self.obj.unblock_signal(self.id);
+    }
+}
+
+// owned by an async signal handler, dropping it when the handler is disconnected cancels any
+// tasks that are still pending
+#[derive(Default)]
+pub struct AsyncHandler {
+    tasks: std::cell::RefCell<Vec<(glib::MainContext, glib::SourceId)>>,
+}
+
+impl AsyncHandler {
+    pub fn spawn<F: std::future::Future<Output = ()> + 'static>(&self, future: F) {
+        let ctx = glib::MainContext::ref_thread_default();
+        let mut tasks = self.tasks.borrow_mut();
+        tasks.retain(|(ctx, id)| ctx.find_source_by_id(id).is_some());
+        let id = ctx.spawn_local(future);
+        tasks.push((ctx, id));
+    }
+}
+
+impl Drop for AsyncHandler {
+    fn drop(&mut self) {
+        for (ctx, id) in self.tasks.take() {
+            if let Some(source) = ctx.find_source_by_id(&id) {
+                source.destroy();
+            }
+        }
+    }
+}
+
+type EmissionHookFn = Box<dyn Fn(&[Value]) -> bool + Send + Sync + 'static>;
+
+// a hook that returns false is removed by GObject, its id must not be removed again after that
+#[derive(Debug, PartialEq, Eq)]
+pub struct EmissionHookId {
+    signal_id: glib::subclass::SignalId,
+    id: std::os::raw::c_ulong,
+}
+
+impl EmissionHookId {
+    pub fn remove(self) {
+        unsafe {
+            glib::gobject_ffi::g_signal_remove_emission_hook(self.signal_id.into_glib(), self.id);
+        }
+    }
+}
+
+pub fn add_emission_hook<F>(
+    signal_id: glib::subclass::SignalId,
+    details: Option<glib::Quark>,
+    f: F,
+) -> EmissionHookId
+where
+    F: Fn(&[Value]) -> bool + Send + Sync + 'static,
+{
+    unsafe extern "C" fn trampoline(
+        _ihint: *mut glib::gobject_ffi::GSignalInvocationHint,
+        n_param_values: std::os::raw::c_uint,
+        param_values: *const glib::gobject_ffi::GValue,
+        data: glib::ffi::gpointer,
+    ) -> glib::ffi::gboolean {
+        let f = &*(data as *const EmissionHookFn);
+        let values =
+            std::slice::from_raw_parts(param_values as *const Value, n_param_values as usize);
+        f(values).into_glib()
+    }
+    unsafe extern "C" fn destroy(data: glib::ffi::gpointer) {
+        drop(Box::from_raw(data as *mut EmissionHookFn));
+    }
+    let f: Box<EmissionHookFn> = Box::new(Box::new(f));
+    let id = unsafe {
+        glib::gobject_ffi::g_signal_add_emission_hook(
+            signal_id.into_glib(),
+            details.map_or(0, |d| d.into_glib()),
+            Some(trampoline),
+            Box::into_raw(f) as glib::ffi::gpointer,
+            Some(destroy),
+        )
+    };
+    EmissionHookId { signal_id, id }
+}
+
+struct SignalFutureState<T> {
+    value: Option<T>,
+    waker: Option<std::task::Waker>,
+    fired: bool,
+    closed: bool,
+}
+
+pub struct SignalSender<T>(std::sync::Arc<std::sync::Mutex<SignalFutureState<T>>>);
+
+impl<T> SignalSender<T> {
+    // only the first emission is kept, the handler is disconnected once the future resolves
+    pub fn send(&self, value: T) {
+        let mut state = self.0.lock().unwrap();
+        if !state.fired {
+            state.fired = true;
+            state.value = Some(value);
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+// the sender lives in the handler closure, which the object drops when it's finalized
+impl<T> Drop for SignalSender<T> {
+    fn drop(&mut self) {
+        let mut state = self.0.lock().unwrap();
+        state.closed = true;
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+// resolves to None if the object is finalized before the signal is emitted
+pub struct SignalFuture<T> {
+    state: std::sync::Arc<std::sync::Mutex<SignalFutureState<T>>>,
+    guard: Option<SignalHandlerGuard>,
+}
+
+impl<T> SignalFuture<T> {
+    pub fn new<O, F>(obj: &O, connect: F) -> Self
+    where
+        O: ObjectType,
+        F: FnOnce(SignalSender<T>) -> glib::SignalHandlerId,
+    {
+        let state = std::sync::Arc::new(std::sync::Mutex::new(SignalFutureState {
+            value: None,
+            waker: None,
+            fired: false,
+            closed: false,
+        }));
+        let id = connect(SignalSender(state.clone()));
+        Self {
+            state,
+            guard: Some(SignalHandlerGuard::new(obj, id)),
+        }
+    }
+}
+
+impl<T> std::future::Future for SignalFuture<T> {
+    type Output = Option<T>;
+
+    fn poll(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<T>> {
+        let value = {
+            let mut state = self.state.lock().unwrap();
+            match state.value.take() {
+                Some(value) => Some(value),
+                None if state.closed => None,
+                None => {
+                    state.waker = Some(cx.waker().clone());
+                    return std::task::Poll::Pending;
+                }
+            }
+        };
+        self.guard.take();
+        std::task::Poll::Ready(value)
+    }
+}
+
+pub struct PropertiesDebug<'a> {
+    obj: &'a glib::Object,
+    pspecs: Vec<&'a ParamSpec>,
+}
+
+impl<'a> PropertiesDebug<'a> {
+    pub fn new<O: ObjectType>(obj: &'a O, pspecs: Vec<&'a ParamSpec>) -> Self {
+        Self {
+            obj: unsafe { glib::Cast::unsafe_cast_ref(obj) },
+            pspecs,
+        }
+    }
+}
+
+struct ValueDebug(Value);
+
+impl std::fmt::Debug for ValueDebug {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let value = &self.0;
+        let type_ = value.type_();
+        if type_ == glib::Type::STRING {
+            return value.get::<Option<&str>>().unwrap().fmt(f);
+        }
+        if type_.is_a(glib::Type::OBJECT) {
+            return match value.get::<Option<glib::Object>>().unwrap() {
+                Some(obj) => write!(f, "{}({:?})", obj.type_(), obj.as_ptr()),
+                None => f.write_str("None"),
+            };
+        }
+        match value_to_string(value) {
+            Some(s) => f.write_str(&s),
+            None => value.fmt(f),
+        }
+    }
+}
+
+impl<'a> std::fmt::Debug for PropertiesDebug<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut s = f.debug_struct(self.obj.type_().name());
+        for pspec in &self.pspecs {
+            if pspec.flags().contains(ParamFlags::READABLE) {
+                s.field(
+                    pspec.name(),
+                    &ValueDebug(self.obj.property_from_pspec(pspec)),
+                );
+            }
+        }
+        s.finish()
+    }
+}
+
+fn pspec_range(pspec: &ParamSpec) -> Option<(String, String)> {
+    macro_rules! range {
+        ($($ty:ty),*) => {
+            $(if let Some(p) = pspec.downcast_ref::<$ty>() {
+                return Some((p.minimum().to_string(), p.maximum().to_string()));
+            })*
+        };
+    }
+    range!(
+        glib::ParamSpecChar,
+        glib::ParamSpecUChar,
+        glib::ParamSpecInt,
+        glib::ParamSpecUInt,
+        glib::ParamSpecLong,
+        glib::ParamSpecULong,
+        glib::ParamSpecInt64,
+        glib::ParamSpecUInt64,
+        glib::ParamSpecFloat,
+        glib::ParamSpecDouble
+    );
+    None
+}
+
+pub fn property_args_help(type_: glib::Type) -> String {
+    let class = match glib::object::ObjectClass::from_type(type_) {
+        Some(class) => class,
+        None => return String::new(),
+    };
+    let mut help = String::new();
+    for pspec in class.list_properties().iter() {
+        if !pspec.flags().contains(ParamFlags::WRITABLE) {
+            continue;
+        }
+        let value_type = pspec.value_type();
+        let metavar = if value_type == glib::Type::BOOL {
+            String::new()
+        } else {
+            format!("=<{}>", value_type)
+        };
+        help.push_str(&format!("  --{}{}\n", pspec.name(), metavar));
+        let mut desc = vec![];
+        if pspec.nick() != pspec.name() {
+            desc.push(pspec.nick().to_owned());
+        }
+        if !pspec.blurb().is_empty() && pspec.blurb() != pspec.nick() {
+            desc.push(pspec.blurb().to_owned());
+        }
+        if let Some(class) = glib::EnumClass::new(value_type) {
+            let nicks = class.values().iter().map(|v| v.nick()).collect::<Vec<_>>();
+            desc.push(format!("one of: {}", nicks.join(", ")));
+        } else if let Some(class) = glib::FlagsClass::new(value_type) {
+            let nicks = class.values().iter().map(|v| v.nick()).collect::<Vec<_>>();
+            desc.push(format!("any of: {}", nicks.join("|")));
+        }
+        if let Some((min, max)) = pspec_range(pspec) {
+            desc.push(format!("range: {}..{}", min, max));
+        }
+        if let Some(default) = value_to_string(pspec.default_value()) {
+            desc.push(format!("default: {}", default));
+        }
+        if !desc.is_empty() {
+            help.push_str(&format!("      {}\n", desc.join("; ")));
+        }
+    }
+    help
+}
+
+// construct properties are set exactly once while the object is constructed, so only that first
+// set is overridden, and only when it carries the default value. a value passed to the
+// constructor that happens to equal the default can't be told apart and is overridden too.
+pub fn construct_env_override<O: ObjectType>(
+    obj: &O,
+    pspec: &ParamSpec,
+    var: &str,
+    value: &Value,
+) -> Option<Value> {
+    let key = glib::Quark::from_str(&format!("gobject-impl-env-{}", pspec.name()));
+    unsafe {
+        if obj.qdata::<()>(key).is_some() {
+            return None;
+        }
+        obj.set_qdata(key, ());
+    }
+    if !values_eq(pspec, value, pspec.default_value()) {
+        return None;
+    }
+    let res = match std::env::var(var) {
+        Ok(text) => value_from_str(pspec, &text).map_err(|err| err.to_string()),
+        Err(std::env::VarError::NotPresent) => return None,
+        Err(err) => Err(err.to_string()),
+    };
+    match res {
+        Ok(value) => Some(value),
+        Err(err) => {
+            glib::g_warning!(
+                "gobject-impl",
+                "Ignoring environment variable {} for property '{}': {}",
+                var,
+                pspec.name(),
+                err
+            );
+            None
+        }
+    }
+}
+
+// construct-only properties can't be changed after the object is created, so they are left out
+fn is_persistable(pspec: &ParamSpec) -> bool {
+    let flags = pspec.flags();
+    flags.contains(ParamFlags::READABLE | ParamFlags::WRITABLE)
+        && !flags.contains(ParamFlags::CONSTRUCT_ONLY)
+}
+
+fn keyfile_set_string_list(keyfile: &glib::KeyFile, group: &str, key: &str, list: &[&str]) {
+    unsafe {
+        glib::ffi::g_key_file_set_string_list(
+            keyfile.to_glib_none().0,
+            group.to_glib_none().0,
+            key.to_glib_none().0,
+            list.to_glib_none().0,
+            list.len(),
+        );
+    }
+}
+
+// NULL strings and variants remove the key, types with no KeyFile equivalent are skipped
+#[allow(clippy::unnecessary_cast)]
+pub fn save_properties_to_keyfile<O: ObjectType>(
+    obj: &O,
+    pspecs: &[&ParamSpec],
+    keyfile: &glib::KeyFile,
+    group: &str,
+) {
+    use glib::{StaticType, Type};
+
+    for pspec in pspecs.iter().copied().filter(|p| is_persistable(p)) {
+        let key = pspec.name();
+        let value = obj.property_from_pspec(pspec);
+        let type_ = value.type_();
+        match type_ {
+            Type::BOOL => keyfile.set_boolean(group, key, value.get().unwrap()),
+            Type::I8 => keyfile.set_integer(group, key, value.get::<i8>().unwrap().into()),
+            Type::U8 => keyfile.set_integer(group, key, value.get::<u8>().unwrap().into()),
+            Type::I32 => keyfile.set_integer(group, key, value.get().unwrap()),
+            Type::U32 => keyfile.set_uint64(group, key, value.get::<u32>().unwrap().into()),
+            Type::I_LONG => {
+                keyfile.set_int64(group, key, value.get::<glib::ILong>().unwrap().0 as i64)
+            }
+            Type::U_LONG => {
+                keyfile.set_uint64(group, key, value.get::<glib::ULong>().unwrap().0 as u64)
+            }
+            Type::I64 => keyfile.set_int64(group, key, value.get().unwrap()),
+            Type::U64 => keyfile.set_uint64(group, key, value.get().unwrap()),
+            Type::F32 => keyfile.set_double(group, key, value.get::<f32>().unwrap().into()),
+            Type::F64 => keyfile.set_double(group, key, value.get().unwrap()),
+            Type::STRING => match value.get::<Option<&str>>().unwrap() {
+                Some(s) => keyfile.set_string(group, key, s),
+                None => {
+                    keyfile.remove_key(group, key).ok();
+                }
+            },
+            Type::VARIANT => match value.get::<Option<glib::Variant>>().unwrap() {
+                Some(v) => keyfile.set_string(group, key, &v.print(false)),
+                None => {
+                    keyfile.remove_key(group, key).ok();
+                }
+            },
+            _ if type_ == Type::static_type() => {
+                keyfile.set_string(group, key, value.get::<Type>().unwrap().name())
+            }
+            _ if type_ == Vec::<String>::static_type() => {
+                let list = value.get::<Vec<String>>().unwrap();
+                let list = list.iter().map(|s| s.as_str()).collect::<Vec<_>>();
+                keyfile_set_string_list(keyfile, group, key, &list);
+            }
+            _ if type_.is_a(Type::ENUM) => {
+                if let Some((_, v)) = glib::EnumValue::from_value(&value) {
+                    keyfile.set_string(group, key, v.nick());
+                }
+            }
+            _ if type_.is_a(Type::FLAGS) => {
+                if let Some((_, values)) = glib::FlagsValue::from_value(&value) {
+                    let list = values.iter().map(|v| v.nick()).collect::<Vec<_>>();
+                    keyfile_set_string_list(keyfile, group, key, &list);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// all values are parsed and validated before any property is set, so the object is left
+// untouched on error. missing keys keep the current value.
+pub fn load_properties_from_keyfile<O: ObjectType>(
+    obj: &O,
+    pspecs: &[&ParamSpec],
+    keyfile: &glib::KeyFile,
+    group: &str,
+) -> Result<(), PropertyError> {
+    use glib::{StaticType, ToValue, Type};
+
+    if is_object_sealed(obj) {
+        return Err(PropertyError::Sealed { type_: obj.type_() });
+    }
+    let mut values = Vec::new();
+    for pspec in pspecs.iter().copied().filter(|p| is_persistable(p)) {
+        let key = pspec.name();
+        if !keyfile.has_key(group, key).unwrap_or(false) {
+            continue;
+        }
+        let parse = |err: glib::Error| PropertyError::Parse {
+            name: key.to_owned(),
+            message: err.message().to_owned(),
+        };
+        let invalid = || PropertyError::InvalidValue {
+            name: key.to_owned(),
+        };
+        let type_ = pspec.value_type();
+        let mut value = match type_ {
+            Type::BOOL => keyfile.boolean(group, key).map_err(parse)?.to_value(),
+            Type::I8 => i8::try_from(keyfile.integer(group, key).map_err(parse)?)
+                .map_err(|_| invalid())?
+                .to_value(),
+            Type::U8 => u8::try_from(keyfile.integer(group, key).map_err(parse)?)
+                .map_err(|_| invalid())?
+                .to_value(),
+            Type::I32 => keyfile.integer(group, key).map_err(parse)?.to_value(),
+            Type::U32 => u32::try_from(keyfile.uint64(group, key).map_err(parse)?)
+                .map_err(|_| invalid())?
+                .to_value(),
+            Type::I_LONG => glib::ILong(
+                std::os::raw::c_long::try_from(keyfile.int64(group, key).map_err(parse)?)
+                    .map_err(|_| invalid())?,
+            )
+            .to_value(),
+            Type::U_LONG => glib::ULong(
+                std::os::raw::c_ulong::try_from(keyfile.uint64(group, key).map_err(parse)?)
+                    .map_err(|_| invalid())?,
+            )
+            .to_value(),
+            Type::I64 => keyfile.int64(group, key).map_err(parse)?.to_value(),
+            Type::U64 => keyfile.uint64(group, key).map_err(parse)?.to_value(),
+            Type::F32 => (keyfile.double(group, key).map_err(parse)? as f32).to_value(),
+            Type::F64 => keyfile.double(group, key).map_err(parse)?.to_value(),
+            Type::STRING => keyfile.string(group, key).map_err(parse)?.to_value(),
+            Type::VARIANT => {
+                let variant_type = pspec
+                    .downcast_ref::<glib::ParamSpecVariant>()
+                    .and_then(|p| p.type_());
+                let text = keyfile.string(group, key).map_err(parse)?;
+                glib::Variant::parse(variant_type, &text)
+                    .map_err(parse)?
+                    .to_value()
+            }
+            _ if type_ == Type::static_type() => {
+                Type::from_name(&keyfile.string(group, key).map_err(parse)?)
+                    .ok_or_else(invalid)?
+                    .to_value()
+            }
+            _ if type_ == Vec::<String>::static_type() => keyfile
+                .string_list(group, key)
+                .map_err(parse)?
+                .iter()
+                .map(|s| s.to_string())
+                .collect::<Vec<_>>()
+                .to_value(),
+            _ if type_.is_a(Type::ENUM) => {
+                let nick = keyfile.string(group, key).map_err(parse)?;
+                glib::EnumClass::new(type_)
+                    .and_then(|c| c.to_value_by_nick(&nick))
+                    .ok_or_else(invalid)?
+            }
+            _ if type_.is_a(Type::FLAGS) => {
+                let nicks = keyfile.string_list(group, key).map_err(parse)?;
+                glib::FlagsClass::new(type_)
+                    .and_then(|c| {
+                        nicks
+                            .iter()
+                            .fold(c.builder(), |b, n| b.set_by_nick(n))
+                            .build()
+                    })
+                    .ok_or_else(invalid)?
+            }
+            _ => continue,
+        };
+        if !validate_value(pspec, &mut value) {
+            return Err(invalid());
+        }
+        values.push((pspec, value));
+    }
+    let _guard = obj.freeze_notify();
+    for (pspec, value) in values {
+        obj.set_property_from_pspec(pspec, value);
+    }
+    Ok(())
+}
+
 pub trait ParamStore {
     type Type: ValueType;
 }
@@ -0,0 +1,290 @@
+use ::serde::{
+    de::{self, DeserializeSeed, MapAccess, Visitor},
+    ser::{self, SerializeMap},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use glib::{prelude::*, ParamFlags, ParamSpec, Type, Value};
+
+use crate::{new_object_with_values, object_value, validate_value, ObjectPspecExt};
+
+pub struct SerializeValue<'a>(pub &'a Value);
+
+impl<'a> Serialize for SerializeValue<'a> {
+    // c_long is 32-bit on some platforms
+    #[allow(clippy::unnecessary_cast)]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let value = self.0;
+        let type_ = value.type_();
+        match type_ {
+            Type::BOOL => serializer.serialize_bool(value.get().unwrap()),
+            Type::I8 => serializer.serialize_i8(value.get().unwrap()),
+            Type::U8 => serializer.serialize_u8(value.get().unwrap()),
+            Type::I32 => serializer.serialize_i32(value.get().unwrap()),
+            Type::U32 => serializer.serialize_u32(value.get().unwrap()),
+            Type::I_LONG => serializer.serialize_i64(value.get::<glib::ILong>().unwrap().0 as i64),
+            Type::U_LONG => serializer.serialize_u64(value.get::<glib::ULong>().unwrap().0 as u64),
+            Type::I64 => serializer.serialize_i64(value.get().unwrap()),
+            Type::U64 => serializer.serialize_u64(value.get().unwrap()),
+            Type::F32 => serializer.serialize_f32(value.get().unwrap()),
+            Type::F64 => serializer.serialize_f64(value.get().unwrap()),
+            Type::STRING => value.get::<Option<&str>>().unwrap().serialize(serializer),
+            Type::VARIANT => value
+                .get::<Option<glib::Variant>>()
+                .unwrap()
+                .map(|v| v.print(true).to_string())
+                .serialize(serializer),
+            _ if type_ == Type::static_type() => {
+                serializer.serialize_str(value.get::<Type>().unwrap().name())
+            }
+            _ if type_ == Vec::<String>::static_type() => {
+                value.get::<Vec<String>>().unwrap().serialize(serializer)
+            }
+            _ if type_.is_a(Type::ENUM) => {
+                let (_, enum_value) = glib::EnumValue::from_value(value).ok_or_else(|| {
+                    ser::Error::custom(format!("invalid value for enum type '{}'", type_))
+                })?;
+                serializer.serialize_str(enum_value.nick())
+            }
+            _ if type_.is_a(Type::FLAGS) => {
+                let (_, flags_values) = glib::FlagsValue::from_value(value).ok_or_else(|| {
+                    ser::Error::custom(format!("invalid value for flags type '{}'", type_))
+                })?;
+                let nicks = flags_values.iter().map(|f| f.nick()).collect::<Vec<_>>();
+                serializer.serialize_str(&nicks.join("|"))
+            }
+            _ if type_.is_a(Type::OBJECT) => match value.get::<Option<glib::Object>>().unwrap() {
+                Some(obj)
+                    if SERIALIZING.with(|s| s.borrow().contains(&(obj.as_ptr() as usize))) =>
+                {
+                    Err(ser::Error::custom(format!(
+                        "reference cycle through object of type '{}'",
+                        obj.type_()
+                    )))
+                }
+                Some(obj) => {
+                    let pspecs = obj.list_properties();
+                    let pspecs = pspecs.iter().collect::<Vec<_>>();
+                    serialize_properties(&obj, &pspecs, serializer)
+                }
+                None => serializer.serialize_none(),
+            },
+            _ => Err(ser::Error::custom(format!(
+                "unsupported property type '{}'",
+                type_
+            ))),
+        }
+    }
+}
+
+thread_local! {
+    static SERIALIZING: std::cell::RefCell<std::collections::HashSet<usize>> = Default::default();
+}
+
+// an object that is reached again while it's being serialized is an error, cycles can't be
+// represented
+pub fn serialize_properties<O: ObjectType, S: Serializer>(
+    obj: &O,
+    pspecs: &[&ParamSpec],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    struct Visit(usize, bool);
+    impl Drop for Visit {
+        fn drop(&mut self) {
+            if self.1 {
+                SERIALIZING.with(|s| s.borrow_mut().remove(&self.0));
+            }
+        }
+    }
+
+    let key = obj.as_ptr() as usize;
+    let _visit = Visit(key, SERIALIZING.with(|s| s.borrow_mut().insert(key)));
+    let mut map = serializer.serialize_map(None)?;
+    for pspec in pspecs {
+        if pspec.flags().contains(ParamFlags::READABLE) {
+            let value = obj.property_from_pspec(pspec);
+            map.serialize_entry(pspec.name(), &SerializeValue(&value))?;
+        }
+    }
+    map.end()
+}
+
+pub fn deserialize_object<'de, O, D>(deserializer: D, pspecs: &[&ParamSpec]) -> Result<O, D::Error>
+where
+    O: IsA<glib::Object>,
+    D: Deserializer<'de>,
+{
+    let obj = ObjectSeed {
+        type_: O::static_type(),
+        pspecs,
+    }
+    .deserialize(deserializer)?;
+    obj.downcast::<O>()
+        .map_err(|obj| de::Error::custom(format!("unexpected object type '{}'", obj.type_())))
+}
+
+struct ObjectSeed<'a> {
+    type_: Type,
+    pspecs: &'a [&'a ParamSpec],
+}
+
+impl<'a, 'de> DeserializeSeed<'de> for ObjectSeed<'a> {
+    type Value = glib::Object;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'a, 'de> Visitor<'de> for ObjectSeed<'a> {
+    type Value = glib::Object;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "a map of properties for type '{}'", self.type_)
+    }
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut values = vec![];
+        while let Some(name) = map.next_key::<String>()? {
+            let pspec = self
+                .pspecs
+                .iter()
+                .find(|p| p.name() == name)
+                .ok_or_else(|| de::Error::unknown_field(&name, &[]))?;
+            if !pspec.flags().contains(ParamFlags::WRITABLE) {
+                map.next_value::<de::IgnoredAny>()?;
+                continue;
+            }
+            let mut value = map.next_value_seed(ValueSeed(pspec))?;
+            if !validate_value(pspec, &mut value) {
+                return Err(de::Error::custom(format!(
+                    "invalid value for property '{}' of type '{}'",
+                    name, self.type_
+                )));
+            }
+            values.push((*pspec, value));
+        }
+        new_object_with_values(self.type_, values).map_err(de::Error::custom)
+    }
+}
+
+struct ValueSeed<'a>(&'a ParamSpec);
+
+// `long` is 32 bits wide on some platforms
+fn long_range<E: de::Error>(pspec: &ParamSpec, value: impl std::fmt::Display) -> E {
+    E::custom(format!(
+        "value {} is out of range for property '{}'",
+        value,
+        pspec.name()
+    ))
+}
+
+impl<'a, 'de> DeserializeSeed<'de> for ValueSeed<'a> {
+    type Value = Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        let pspec = self.0;
+        let type_ = pspec.value_type();
+        Ok(match type_ {
+            Type::BOOL => bool::deserialize(deserializer)?.to_value(),
+            Type::I8 => i8::deserialize(deserializer)?.to_value(),
+            Type::U8 => u8::deserialize(deserializer)?.to_value(),
+            Type::I32 => i32::deserialize(deserializer)?.to_value(),
+            Type::U32 => u32::deserialize(deserializer)?.to_value(),
+            Type::I_LONG => {
+                let value = i64::deserialize(deserializer)?;
+                glib::ILong(value.try_into().map_err(|_| long_range(pspec, value))?).to_value()
+            }
+            Type::U_LONG => {
+                let value = u64::deserialize(deserializer)?;
+                glib::ULong(value.try_into().map_err(|_| long_range(pspec, value))?).to_value()
+            }
+            Type::I64 => i64::deserialize(deserializer)?.to_value(),
+            Type::U64 => u64::deserialize(deserializer)?.to_value(),
+            Type::F32 => f32::deserialize(deserializer)?.to_value(),
+            Type::F64 => f64::deserialize(deserializer)?.to_value(),
+            Type::STRING => Option::<String>::deserialize(deserializer)?.to_value(),
+            Type::VARIANT => {
+                let variant_type = pspec
+                    .downcast_ref::<glib::ParamSpecVariant>()
+                    .and_then(|p| p.type_());
+                Option::<String>::deserialize(deserializer)?
+                    .map(|s| glib::Variant::parse(variant_type, &s))
+                    .transpose()
+                    .map_err(de::Error::custom)?
+                    .to_value()
+            }
+            _ if type_ == Type::static_type() => {
+                let name = String::deserialize(deserializer)?;
+                Type::from_name(&name)
+                    .ok_or_else(|| de::Error::custom(format!("unknown type '{}'", name)))?
+                    .to_value()
+            }
+            _ if type_ == Vec::<String>::static_type() => {
+                Vec::<String>::deserialize(deserializer)?.to_value()
+            }
+            _ if type_.is_a(Type::ENUM) => {
+                let nick = String::deserialize(deserializer)?;
+                glib::EnumClass::new(type_)
+                    .and_then(|c| c.to_value_by_nick(&nick))
+                    .ok_or_else(|| {
+                        de::Error::custom(format!("invalid nick '{}' for enum '{}'", nick, type_))
+                    })?
+            }
+            _ if type_.is_a(Type::FLAGS) => {
+                let nicks = String::deserialize(deserializer)?;
+                glib::FlagsClass::new(type_)
+                    .and_then(|c| {
+                        nicks
+                            .split('|')
+                            .map(|n| n.trim())
+                            .filter(|n| !n.is_empty())
+                            .fold(c.builder(), |b, n| b.set_by_nick(n))
+                            .build()
+                    })
+                    .ok_or_else(|| {
+                        de::Error::custom(format!(
+                            "invalid nicks '{}' for flags '{}'",
+                            nicks, type_
+                        ))
+                    })?
+            }
+            _ if type_.is_a(Type::OBJECT) => {
+                deserializer.deserialize_option(ObjectValueVisitor(type_))?
+            }
+            _ => {
+                return Err(de::Error::custom(format!(
+                    "unsupported property type '{}'",
+                    type_
+                )))
+            }
+        })
+    }
+}
+
+struct ObjectValueVisitor(Type);
+
+impl<'de> Visitor<'de> for ObjectValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "an optional map of properties for type '{}'", self.0)
+    }
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(Value::from_type(self.0))
+    }
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(Value::from_type(self.0))
+    }
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        let class = glib::object::ObjectClass::from_type(self.0).ok_or_else(|| {
+            de::Error::custom(format!("can't retrieve class for type '{}'", self.0))
+        })?;
+        let pspecs = class.list_properties();
+        let pspecs = pspecs.iter().collect::<Vec<_>>();
+        let obj = ObjectSeed {
+            type_: self.0,
+            pspecs: &pspecs,
+        }
+        .deserialize(deserializer)?;
+        Ok(object_value(self.0, &obj))
+    }
+}
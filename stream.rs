@@ -0,0 +1,79 @@
+use futures_core::Stream;
+use std::{
+    collections::VecDeque,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+};
+
+use crate::SignalHandlerGuard;
+
+struct SignalStreamState<T> {
+    queue: VecDeque<T>,
+    waker: Option<Waker>,
+    closed: bool,
+}
+
+pub struct StreamSender<T>(Arc<Mutex<SignalStreamState<T>>>);
+
+impl<T> StreamSender<T> {
+    pub fn send(&self, value: T) {
+        let mut state = self.0.lock().unwrap();
+        state.queue.push_back(value);
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+// the sender lives in the handler closure, which the object drops when it's finalized
+impl<T> Drop for StreamSender<T> {
+    fn drop(&mut self) {
+        let mut state = self.0.lock().unwrap();
+        state.closed = true;
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+// ends once the object is finalized, the handler is disconnected when the stream is dropped
+pub struct SignalStream<T> {
+    state: Arc<Mutex<SignalStreamState<T>>>,
+    _guard: SignalHandlerGuard,
+}
+
+impl<T> SignalStream<T> {
+    pub fn new<O, F>(obj: &O, connect: F) -> Self
+    where
+        O: glib::ObjectType,
+        F: FnOnce(StreamSender<T>) -> glib::SignalHandlerId,
+    {
+        let state = Arc::new(Mutex::new(SignalStreamState {
+            queue: VecDeque::new(),
+            waker: None,
+            closed: false,
+        }));
+        let id = connect(StreamSender(state.clone()));
+        Self {
+            state,
+            _guard: SignalHandlerGuard::new(obj, id),
+        }
+    }
+}
+
+impl<T> Stream for SignalStream<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let mut state = self.state.lock().unwrap();
+        match state.queue.pop_front() {
+            Some(value) => Poll::Ready(Some(value)),
+            None if state.closed => Poll::Ready(None),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
@@ -214,6 +214,19 @@ fn complex_properties() {
     let obj = glib::Object::new::<ComplexProps>(&[("dummy", &dummy)]).unwrap();
     obj.set_renamed_string("hello".into());
     assert_eq!(&*obj.dummy().renamed_string(), "foobar");
+
+    let notified = std::rc::Rc::new(Cell::new(0));
+    dummy.connect_notify_local(Some("renamed-string"), {
+        let notified = notified.clone();
+        move |_, _| notified.set(notified.get() + 1)
+    });
+    BaseObjectExt::set_renamed_string(dummy.upcast_ref::<BaseObject>(), "abstract".into());
+    assert_eq!(notified.get(), 1);
+    assert_eq!(dummy.renamed_string(), "abstract");
+    assert_eq!(
+        BaseObjectExt::renamed_string(dummy.upcast_ref::<BaseObject>()),
+        "abstract"
+    );
 }
 
 #[test]
@@ -263,3 +276,1123 @@ fn pod_type() {
     obj.set_int_prop(5);
     obj.set_string_prop("123".into());
 }
+
+#[test]
+fn batch_update() {
+    wrapper!(Batched(BatchedPrivate));
+    #[object_impl(trait = BatchedExt, batch)]
+    impl ObjectImpl for BatchedPrivate {
+        properties! {
+            #[derive(Default)]
+            pub struct BatchedPrivate {
+                #[property(get, set)]
+                my_i32: Cell<i32>,
+                #[property(get, set)]
+                my_str: RefCell<String>,
+                #[property(get, set, set_inline)]
+                my_inline: Cell<u64>,
+                #[property(get, set, construct_only)]
+                my_construct_only: Cell<u8>,
+            }
+        }
+    }
+
+    let obj = glib::Object::new::<Batched>(&[]).unwrap();
+    let log = std::rc::Rc::new(RefCell::new(Vec::<String>::new()));
+    obj.connect_notify_local(None, {
+        let log = log.clone();
+        move |_, pspec| log.borrow_mut().push(pspec.name().to_owned())
+    });
+    obj.update(|batch| {
+        batch.set_my_i32(1);
+        batch.set_my_str("first".into());
+        batch.set_my_i32(2);
+        batch.set_my_inline(3);
+        batch.set_my_str("second".into());
+        assert!(log.borrow().is_empty());
+    });
+    let mut names = log.borrow().clone();
+    names.sort();
+    assert_eq!(names, ["my-i32", "my-inline", "my-str"]);
+    assert_eq!(obj.my_i32(), 2);
+    assert_eq!(obj.my_str(), "second");
+    assert_eq!(obj.my_inline(), 3);
+
+    wrapper!(FinalBatched(FinalBatchedPrivate));
+    #[object_impl(final, type = FinalBatched, batch)]
+    impl ObjectImpl for FinalBatchedPrivate {
+        properties! {
+            #[derive(Default)]
+            pub struct FinalBatchedPrivate {
+                #[property(get, set)]
+                my_i32: Cell<i32>,
+            }
+        }
+    }
+
+    let obj = glib::Object::new::<FinalBatched>(&[]).unwrap();
+    let count = std::rc::Rc::new(std::cell::Cell::new(0));
+    obj.connect_notify_local(None, {
+        let count = count.clone();
+        move |_, _| count.set(count.get() + 1)
+    });
+    obj.update(|batch| {
+        batch.set_my_i32(1);
+        batch.set_my_i32(2);
+    });
+    assert_eq!(obj.my_i32(), 2);
+    assert_eq!(count.get(), 1);
+}
+
+#[test]
+fn snapshot() {
+    wrapper!(Snapshotted(SnapshottedPrivate));
+    #[object_impl(final, type = Snapshotted, snapshot(Debug, PartialEq))]
+    impl ObjectImpl for SnapshottedPrivate {
+        properties! {
+            #[derive(Default)]
+            pub struct SnapshottedPrivate {
+                #[property(get)]
+                readable_i32: Cell<i32>,
+                #[property(get, set)]
+                my_i32: Cell<i32>,
+                #[property(get, set)]
+                my_str: RefCell<String>,
+                #[property(get, set, construct_only, default = 4)]
+                my_construct_only: Cell<u8>,
+            }
+        }
+    }
+
+    let obj = glib::Object::new::<Snapshotted>(&[]).unwrap();
+    obj.set_my_i32(5);
+    obj.set_my_str("before".into());
+    let state = obj.snapshot();
+    assert_eq!(
+        state,
+        SnapshottedState {
+            readable_i32: 0,
+            my_i32: 5,
+            my_str: "before".into(),
+            my_construct_only: 4,
+        }
+    );
+
+    obj.set_my_i32(10);
+    obj.set_my_str("after".into());
+    assert_ne!(obj.snapshot(), state);
+
+    let count = std::rc::Rc::new(Cell::new(0));
+    obj.connect_notify_local(None, {
+        let count = count.clone();
+        move |_, _| count.set(count.get() + 1)
+    });
+    obj.apply(&state);
+    assert_eq!(count.get(), 2);
+    assert_eq!(obj.snapshot(), state);
+
+    #[derive(Clone, Default, glib::Boxed)]
+    #[boxed_type(name = "SnapshotOpaque")]
+    pub struct Opaque(u32);
+
+    wrapper!(PlainSnapshotted(PlainSnapshottedPrivate));
+    #[object_impl(trait = PlainSnapshottedExt, snapshot)]
+    impl ObjectImpl for PlainSnapshottedPrivate {
+        properties! {
+            #[derive(Default)]
+            pub struct PlainSnapshottedPrivate {
+                #[property(get, set, boxed)]
+                opaque: RefCell<Opaque>,
+            }
+        }
+    }
+
+    let obj = glib::Object::new::<PlainSnapshotted>(&[]).unwrap();
+    obj.set_opaque(Opaque(3));
+    let state: PlainSnapshottedState = obj.snapshot().clone();
+    obj.set_opaque(Opaque(1));
+    obj.apply(&state);
+    assert_eq!(obj.opaque().0, 3);
+}
+
+#[test]
+fn variant_dict() {
+    use glib::once_cell::unsync::OnceCell;
+    use gobject_impl::PropertyError;
+
+    #[derive(Debug, Default, Eq, PartialEq, Clone, Copy, glib::Enum)]
+    #[repr(u32)]
+    #[enum_type(name = "TestVariantDictColor")]
+    pub enum Color {
+        #[default]
+        Red,
+        Green,
+    }
+
+    wrapper!(DictChild(DictChildPrivate));
+    #[object_impl(final, type = DictChild, variant_dict)]
+    impl ObjectImpl for DictChildPrivate {
+        properties! {
+            #[derive(Default)]
+            pub struct DictChildPrivate {
+                #[property(get, set)]
+                label: RefCell<String>,
+            }
+        }
+    }
+
+    wrapper!(Dicted(DictedPrivate));
+    impl Default for DictedPrivate {
+        fn default() -> Self {
+            Self {
+                my_i8: Default::default(),
+                my_u64: Default::default(),
+                my_str: Default::default(),
+                color: Default::default(),
+                binding_flags: Cell::new(glib::BindingFlags::empty()),
+                variant: Default::default(),
+                child: Default::default(),
+                readable_i32: Default::default(),
+            }
+        }
+    }
+    #[object_impl(trait = DictedExt, variant_dict)]
+    impl ObjectImpl for DictedPrivate {
+        properties! {
+            pub struct DictedPrivate {
+                #[property(get, set)]
+                my_i8: Cell<i8>,
+                #[property(get, set)]
+                my_u64: Cell<u64>,
+                #[property(get, set)]
+                my_str: RefCell<Option<String>>,
+                #[property(get, set, enum)]
+                color: Cell<Color>,
+                #[property(get, set, flags)]
+                binding_flags: Cell<glib::BindingFlags>,
+                #[property(get, set, variant = "(us)")]
+                variant: RefCell<Option<glib::Variant>>,
+                #[property(get, set, object, construct_only)]
+                child: OnceCell<Option<DictChild>>,
+                #[property(get)]
+                readable_i32: Cell<i32>,
+            }
+        }
+    }
+
+    let child = glib::Object::new::<DictChild>(&[("label", &"nested")]).unwrap();
+    let obj = glib::Object::new::<Dicted>(&[("child", &child)]).unwrap();
+    obj.set_my_i8(-3);
+    obj.set_my_u64(u64::MAX);
+    obj.set_color(Color::Green);
+    obj.set_binding_flags(glib::BindingFlags::INVERT_BOOLEAN);
+    obj.set_variant(Some((7u32, "seven").to_variant()));
+
+    let dict = obj.to_variant_dict();
+    assert_eq!(dict.type_(), glib::VariantTy::VARDICT);
+    let lookup = glib::VariantDict::new(Some(&dict));
+    assert!(!lookup.contains("my-str"));
+    assert_eq!(lookup.lookup::<String>("color").unwrap().unwrap(), "green");
+    assert_eq!(
+        lookup
+            .lookup::<Vec<String>>("binding-flags")
+            .unwrap()
+            .unwrap(),
+        vec!["invert-boolean".to_owned()]
+    );
+    assert_eq!(
+        lookup.lookup_value("variant", None).unwrap(),
+        (7u32, "seven").to_variant()
+    );
+    assert_eq!(
+        lookup.lookup_value("child", None).unwrap(),
+        child.to_variant_dict()
+    );
+
+    let copy = Dicted::from_variant_dict(&dict).unwrap();
+    assert_eq!(copy.my_i8(), -3);
+    assert_eq!(copy.my_u64(), u64::MAX);
+    assert_eq!(copy.my_str(), None);
+    assert_eq!(copy.color(), Color::Green);
+    assert_eq!(copy.binding_flags(), glib::BindingFlags::INVERT_BOOLEAN);
+    assert_eq!(copy.variant(), obj.variant());
+    assert_eq!(copy.child().unwrap().label(), "nested");
+    assert_eq!(copy.to_variant_dict(), dict);
+
+    let bad = glib::VariantDict::new(None);
+    bad.insert("bogus", &1i32);
+    assert!(matches!(
+        Dicted::from_variant_dict(&bad.end()),
+        Err(PropertyError::UnknownProperty { name, .. }) if name == "bogus"
+    ));
+    let bad = glib::VariantDict::new(None);
+    bad.insert("my-u64", &"string");
+    assert!(matches!(
+        Dicted::from_variant_dict(&bad.end()),
+        Err(PropertyError::TypeMismatch { name, expected, found })
+            if name == "my-u64" && expected == "t" && found == "s"
+    ));
+    let bad = glib::VariantDict::new(None);
+    bad.insert("variant", &1i32);
+    assert!(matches!(
+        Dicted::from_variant_dict(&bad.end()),
+        Err(PropertyError::TypeMismatch { name, .. }) if name == "variant"
+    ));
+    assert!(matches!(
+        Dicted::from_variant_dict(&1i32.to_variant()),
+        Err(PropertyError::TypeMismatch { .. })
+    ));
+
+    // only the properties declared on `Dicted` are included, not ones added by subclasses
+    unsafe impl<T: ObjectImpl> glib::subclass::types::IsSubclassable<T> for Dicted {}
+    glib::wrapper! {
+        pub struct DictedSub(ObjectSubclass<DictedSubPrivate>) @extends Dicted;
+    }
+    #[glib::object_subclass]
+    impl ObjectSubclass for DictedSubPrivate {
+        const NAME: &'static str = "DictedSub";
+        type Type = DictedSub;
+        type ParentType = Dicted;
+    }
+    #[object_impl(final, type = DictedSub)]
+    impl ObjectImpl for DictedSubPrivate {
+        properties! {
+            #[derive(Default)]
+            pub struct DictedSubPrivate {
+                #[property(get, set)]
+                extra: Cell<u32>,
+            }
+        }
+    }
+    let sub = glib::Object::new::<DictedSub>(&[]).unwrap();
+    sub.set_extra(5);
+    let lookup = glib::VariantDict::new(Some(&sub.to_variant_dict()));
+    assert!(lookup.contains("my-i8"));
+    assert!(!lookup.contains("extra"));
+
+    wrapper!(Linked(LinkedPrivate));
+    #[object_impl(final, type = Linked, variant_dict)]
+    impl ObjectImpl for LinkedPrivate {
+        properties! {
+            #[derive(Default)]
+            pub struct LinkedPrivate {
+                #[property(get, set)]
+                label: RefCell<String>,
+                #[property(get, set, object)]
+                peer: RefCell<Option<Linked>>,
+            }
+        }
+    }
+    let a = glib::Object::new::<Linked>(&[("label", &"a")]).unwrap();
+    let b = glib::Object::new::<Linked>(&[("label", &"b")]).unwrap();
+    a.set_peer(Some(b.clone()));
+    b.set_peer(Some(a.clone()));
+    let lookup = glib::VariantDict::new(Some(&a.to_variant_dict()));
+    let peer = glib::VariantDict::new(Some(&lookup.lookup_value("peer", None).unwrap()));
+    assert_eq!(
+        peer.lookup::<String>("label").unwrap().as_deref(),
+        Some("b")
+    );
+    assert!(!peer.contains("peer"));
+    a.set_peer(None);
+}
+
+#[test]
+fn keyfile() {
+    use gobject_impl::PropertyError;
+
+    #[derive(Debug, Default, Eq, PartialEq, Clone, Copy, glib::Enum)]
+    #[repr(u32)]
+    #[enum_type(name = "TestKeyFileShape")]
+    pub enum Shape {
+        #[default]
+        Square,
+        Circle,
+    }
+
+    wrapper!(Persisted(PersistedPrivate));
+    impl Default for PersistedPrivate {
+        fn default() -> Self {
+            Self {
+                my_bool: Default::default(),
+                my_u8: Default::default(),
+                my_i64: Default::default(),
+                my_f64: Default::default(),
+                my_str: Default::default(),
+                my_strv: Default::default(),
+                shape: Default::default(),
+                binding_flags: Cell::new(glib::BindingFlags::empty()),
+                transient: Default::default(),
+                readable_i32: Default::default(),
+                my_construct_only: Default::default(),
+            }
+        }
+    }
+    #[object_impl(trait = PersistedExt, keyfile)]
+    impl ObjectImpl for PersistedPrivate {
+        properties! {
+            pub struct PersistedPrivate {
+                #[property(get, set)]
+                my_bool: Cell<bool>,
+                #[property(get, set, maximum = 100)]
+                my_u8: Cell<u8>,
+                #[property(get, set)]
+                my_i64: Cell<i64>,
+                #[property(get, set)]
+                my_f64: Cell<f64>,
+                #[property(get, set)]
+                my_str: RefCell<Option<String>>,
+                #[property(get, set, boxed)]
+                my_strv: RefCell<Vec<String>>,
+                #[property(get, set, enum)]
+                shape: Cell<Shape>,
+                #[property(get, set, flags)]
+                binding_flags: Cell<glib::BindingFlags>,
+                #[property(get, set, persist = false)]
+                transient: Cell<i32>,
+                #[property(get)]
+                readable_i32: Cell<i32>,
+                #[property(get, set, construct_only)]
+                my_construct_only: Cell<i32>,
+            }
+        }
+    }
+
+    let obj = glib::Object::new::<Persisted>(&[]).unwrap();
+    obj.set_my_bool(true);
+    obj.set_my_u8(42);
+    obj.set_my_i64(-1 << 40);
+    obj.set_my_f64(0.5);
+    obj.set_my_str(Some("a string".into()));
+    obj.set_my_strv(vec!["one".into(), "two".into()]);
+    obj.set_shape(Shape::Circle);
+    obj.set_binding_flags(glib::BindingFlags::SYNC_CREATE | glib::BindingFlags::BIDIRECTIONAL);
+    obj.set_transient(5);
+
+    let keyfile = glib::KeyFile::new();
+    obj.save_to_keyfile(&keyfile, "persisted");
+    assert_eq!(keyfile.string("persisted", "shape").unwrap(), "circle");
+    assert_eq!(keyfile.integer("persisted", "my-u8").unwrap(), 42);
+    assert!(!keyfile.has_key("persisted", "transient").unwrap());
+    assert!(!keyfile.has_key("persisted", "readable-i32").unwrap());
+    assert!(!keyfile.has_key("persisted", "my-construct-only").unwrap());
+
+    let data = keyfile.to_data();
+    let keyfile = glib::KeyFile::new();
+    keyfile
+        .load_from_data(&data, glib::KeyFileFlags::NONE)
+        .unwrap();
+    let copy = glib::Object::new::<Persisted>(&[]).unwrap();
+    copy.load_from_keyfile(&keyfile, "persisted").unwrap();
+    assert!(copy.my_bool());
+    assert_eq!(copy.my_u8(), 42);
+    assert_eq!(copy.my_i64(), -1 << 40);
+    assert_eq!(copy.my_f64(), 0.5);
+    assert_eq!(copy.my_str().as_deref(), Some("a string"));
+    assert_eq!(copy.my_strv(), vec!["one".to_owned(), "two".to_owned()]);
+    assert_eq!(copy.shape(), Shape::Circle);
+    assert_eq!(copy.binding_flags(), obj.binding_flags());
+    assert_eq!(copy.transient(), 0);
+
+    // a missing group or key keeps the current values
+    copy.load_from_keyfile(&glib::KeyFile::new(), "persisted")
+        .unwrap();
+    assert_eq!(copy.my_u8(), 42);
+
+    keyfile.set_integer("persisted", "my-u8", 101);
+    keyfile.set_boolean("persisted", "my-bool", false);
+    assert!(matches!(
+        copy.load_from_keyfile(&keyfile, "persisted"),
+        Err(PropertyError::InvalidValue { name }) if name == "my-u8"
+    ));
+    assert!(copy.my_bool());
+    keyfile.set_string("persisted", "my-u8", "many");
+    assert!(matches!(
+        copy.load_from_keyfile(&keyfile, "persisted"),
+        Err(PropertyError::Parse { name, .. }) if name == "my-u8"
+    ));
+}
+
+#[test]
+fn set_from_str() {
+    use gobject_impl::{PropertyError, PropertyFromStrExt};
+
+    #[derive(Debug, Default, Eq, PartialEq, Clone, Copy, glib::Enum)]
+    #[repr(u32)]
+    #[enum_type(name = "TestFromStrSize")]
+    pub enum Size {
+        #[default]
+        Small,
+        Large,
+    }
+
+    wrapper!(Parsed(ParsedPrivate));
+    impl Default for ParsedPrivate {
+        fn default() -> Self {
+            Self {
+                my_bool: Default::default(),
+                my_attributed: Default::default(),
+                my_f32: Default::default(),
+                my_str: Default::default(),
+                size: Default::default(),
+                binding_flags: Cell::new(glib::BindingFlags::empty()),
+                variant: Default::default(),
+                object_type: Cell::new(glib::Object::static_type()),
+                readable_i32: Default::default(),
+            }
+        }
+    }
+    #[object_impl(final, type = Parsed)]
+    impl ObjectImpl for ParsedPrivate {
+        properties! {
+            pub struct ParsedPrivate {
+                #[property(get, set)]
+                my_bool: Cell<bool>,
+                #[property(get, set, name = "my-u8", minimum = 5, maximum = 20, default = 5)]
+                my_attributed: Cell<u8>,
+                #[property(get, set)]
+                my_f32: Cell<f32>,
+                #[property(get, set)]
+                my_str: RefCell<Option<String>>,
+                #[property(get, set, enum)]
+                size: Cell<Size>,
+                #[property(get, set, flags)]
+                binding_flags: Cell<glib::BindingFlags>,
+                #[property(get, set, variant = "(ib)")]
+                variant: RefCell<Option<glib::Variant>>,
+                #[property(get, set, subtype = glib::Object)]
+                object_type: Cell<glib::Type>,
+                #[property(get)]
+                readable_i32: Cell<i32>,
+            }
+        }
+    }
+
+    let obj = glib::Object::new::<Parsed>(&[]).unwrap();
+    obj.set_property_from_str("my-bool", "true").unwrap();
+    assert!(obj.my_bool());
+    obj.set_property_from_str("my-u8", "19").unwrap();
+    assert_eq!(obj.my_u8(), 19);
+    obj.set_property_from_str("my-f32", " 1.5 ").unwrap();
+    assert_eq!(obj.my_f32(), 1.5);
+    obj.set_property_from_str("my-str", "hello").unwrap();
+    assert_eq!(obj.my_str().as_deref(), Some("hello"));
+    obj.set_property_from_str("size", "large").unwrap();
+    assert_eq!(obj.size(), Size::Large);
+    obj.set_property_from_str("binding-flags", "bidirectional | G_BINDING_SYNC_CREATE")
+        .unwrap();
+    assert_eq!(
+        obj.binding_flags(),
+        glib::BindingFlags::BIDIRECTIONAL | glib::BindingFlags::SYNC_CREATE
+    );
+    obj.set_property_from_str("variant", "(3, true)").unwrap();
+    assert_eq!(obj.variant(), Some((3i32, true).to_variant()));
+    obj.set_property_from_str("object-type", "Parsed").unwrap();
+    assert_eq!(obj.object_type(), Parsed::static_type());
+
+    assert!(matches!(
+        obj.set_property_from_str("bogus", "1"),
+        Err(PropertyError::UnknownProperty { name, .. }) if name == "bogus"
+    ));
+    assert!(matches!(
+        obj.set_property_from_str("readable-i32", "1"),
+        Err(PropertyError::NotWritable { name }) if name == "readable-i32"
+    ));
+    assert!(matches!(
+        obj.set_property_from_str("my-u8", "nineteen"),
+        Err(PropertyError::Parse { name, .. }) if name == "my-u8"
+    ));
+    assert!(matches!(
+        obj.set_property_from_str("my-u8", "21"),
+        Err(PropertyError::InvalidValue { name }) if name == "my-u8"
+    ));
+    assert!(matches!(
+        obj.set_property_from_str("size", "medium"),
+        Err(PropertyError::Parse { .. })
+    ));
+    assert!(matches!(
+        obj.set_property_from_str("variant", "(3, 'x')"),
+        Err(PropertyError::Parse { .. })
+    ));
+    assert!(matches!(
+        obj.set_property_from_str("object-type", "GVariant"),
+        Err(PropertyError::InvalidValue { .. })
+    ));
+    assert_eq!(obj.my_u8(), 19);
+}
+
+#[test]
+fn property_args() {
+    use gobject_impl::PropertyError;
+
+    wrapper!(Tool(ToolPrivate));
+    #[object_impl(final, type = Tool)]
+    impl ObjectImpl for ToolPrivate {
+        properties! {
+            #[derive(Default)]
+            pub struct ToolPrivate {
+                #[property(get, set, construct_only, nick = "Verbose", blurb = "Print more output")]
+                verbose: Cell<bool>,
+                #[property(get, set, construct, minimum = 1, maximum = 64, default = 4)]
+                jobs: Cell<u32>,
+                #[property(get, set)]
+                output: RefCell<Option<String>>,
+                #[property(get)]
+                readable_i32: Cell<i32>,
+            }
+        }
+    }
+
+    let (tool, rest) = gobject_impl::object_from_args::<Tool, _, _>([
+        "--verbose",
+        "input.txt",
+        "--jobs=8",
+        "--output",
+        "out.txt",
+        "--",
+        "--jobs=2",
+    ])
+    .unwrap();
+    assert!(tool.verbose());
+    assert_eq!(tool.jobs(), 8);
+    assert_eq!(tool.output().as_deref(), Some("out.txt"));
+    assert_eq!(rest, vec!["input.txt".to_owned(), "--jobs=2".to_owned()]);
+
+    let (tool, rest) = gobject_impl::object_from_args::<Tool, _, &str>([]).unwrap();
+    assert_eq!(tool.jobs(), 4);
+    assert!(rest.is_empty());
+
+    assert!(matches!(
+        gobject_impl::object_from_args::<Tool, _, _>(["--bogus=1"]),
+        Err(PropertyError::UnknownProperty { name, .. }) if name == "bogus"
+    ));
+    assert!(matches!(
+        gobject_impl::object_from_args::<Tool, _, _>(["--readable-i32=1"]),
+        Err(PropertyError::NotWritable { .. })
+    ));
+    assert!(matches!(
+        gobject_impl::object_from_args::<Tool, _, _>(["--jobs=100"]),
+        Err(PropertyError::InvalidValue { name }) if name == "jobs"
+    ));
+    assert!(matches!(
+        gobject_impl::object_from_args::<Tool, _, _>(["--output"]),
+        Err(PropertyError::Parse { name, .. }) if name == "output"
+    ));
+
+    let help = gobject_impl::property_args_help(Tool::static_type());
+    assert!(help.contains("  --verbose\n      Verbose; Print more output; default: FALSE\n"));
+    assert!(help.contains("  --jobs=<guint>\n      range: 1..64; default: 4\n"));
+    assert!(help.contains("  --output=<gchararray>\n"));
+    assert!(!help.contains("readable-i32"));
+}
+
+#[test]
+fn env_defaults() {
+    wrapper!(EnvProps(EnvPropsPrivate));
+    #[object_impl(final, type = EnvProps)]
+    impl ObjectImpl for EnvPropsPrivate {
+        properties! {
+            #[derive(Default)]
+            pub struct EnvPropsPrivate {
+                #[property(get, set, construct, env = "GOBJECT_IMPL_TEST_TIMEOUT", default = 30)]
+                timeout: Cell<u32>,
+                #[property(get, set, construct_only, env = "GOBJECT_IMPL_TEST_HOST")]
+                host: RefCell<Option<String>>,
+                #[property(get, set, construct, env = "GOBJECT_IMPL_TEST_BAD", default = 1.5)]
+                ratio: Cell<f64>,
+                #[property(get, set, construct, env = "GOBJECT_IMPL_TEST_UNSET", default = true)]
+                enabled: Cell<bool>,
+            }
+        }
+    }
+
+    // each variable is only used by this test and removed again at the end
+    std::env::remove_var("GOBJECT_IMPL_TEST_TIMEOUT");
+    std::env::remove_var("GOBJECT_IMPL_TEST_UNSET");
+    let obj = glib::Object::new::<EnvProps>(&[]).unwrap();
+    assert_eq!(obj.timeout(), 30);
+
+    std::env::set_var("GOBJECT_IMPL_TEST_TIMEOUT", "90");
+    std::env::set_var("GOBJECT_IMPL_TEST_HOST", "example.com");
+    std::env::set_var("GOBJECT_IMPL_TEST_BAD", "not a number");
+
+    let obj = glib::Object::new::<EnvProps>(&[]).unwrap();
+    assert_eq!(obj.timeout(), 90);
+    assert_eq!(obj.host().as_deref(), Some("example.com"));
+    assert_eq!(obj.ratio(), 1.5);
+    assert!(obj.enabled());
+    assert_eq!(
+        EnvProps::pspec_timeout().default_value().get::<u32>(),
+        Ok(30)
+    );
+
+    obj.set_timeout(30);
+    assert_eq!(obj.timeout(), 30);
+
+    let obj = glib::Object::new::<EnvProps>(&[("timeout", &5u32)]).unwrap();
+    assert_eq!(obj.timeout(), 5);
+
+    std::env::remove_var("GOBJECT_IMPL_TEST_TIMEOUT");
+    std::env::remove_var("GOBJECT_IMPL_TEST_HOST");
+    std::env::remove_var("GOBJECT_IMPL_TEST_BAD");
+}
+
+#[test]
+fn debug_properties() {
+    #[derive(Debug, Default, Eq, PartialEq, Clone, Copy, glib::Enum)]
+    #[repr(u32)]
+    #[enum_type(name = "TestDebugMode")]
+    pub enum Mode {
+        #[default]
+        Fast,
+        Slow,
+    }
+
+    wrapper!(Debugged(DebuggedPrivate));
+    #[object_impl(trait = DebuggedExt, debug)]
+    impl ObjectImpl for DebuggedPrivate {
+        properties! {
+            #[derive(Default)]
+            pub struct DebuggedPrivate {
+                #[property(get, set)]
+                my_i32: Cell<i32>,
+                #[property(get, set)]
+                user: RefCell<Option<String>>,
+                #[property(get, set, debug = false)]
+                password: RefCell<Option<String>>,
+                #[property(get, set, enum)]
+                mode: Cell<Mode>,
+                #[property(set)]
+                writable_i32: Cell<i32>,
+                #[property(get, set, object)]
+                child: RefCell<Option<glib::Object>>,
+            }
+        }
+    }
+
+    let obj = glib::Object::new::<Debugged>(&[]).unwrap();
+    obj.set_my_i32(3);
+    obj.set_user(Some("admin".into()));
+    obj.set_password(Some("hunter2".into()));
+    obj.set_mode(Mode::Slow);
+    assert_eq!(
+        format!("{:?}", obj.debug_properties()),
+        r#"Debugged { my-i32: 3, user: Some("admin"), mode: slow, child: None }"#
+    );
+    assert_eq!(
+        format!("{:?}", obj.imp()),
+        format!("{:?}", obj.debug_properties())
+    );
+}
+
+#[test]
+fn props_eq() {
+    use std::hash::Hasher;
+
+    wrapper!(Compared(ComparedPrivate));
+    #[object_impl(trait = ComparedExt, props_eq)]
+    impl ObjectImpl for ComparedPrivate {
+        properties! {
+            #[derive(Default)]
+            pub struct ComparedPrivate {
+                #[property(get, set)]
+                my_i32: Cell<i32>,
+                #[property(get, set)]
+                my_str: RefCell<String>,
+                #[property(get, set)]
+                my_f64: Cell<f64>,
+                #[property(set)]
+                writable_i32: Cell<i32>,
+                #[property(get, set, boxed)]
+                my_strv: RefCell<Vec<String>>,
+            }
+        }
+    }
+
+    fn digest(obj: &Compared) -> u64 {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        obj.props_hash(&mut hasher);
+        hasher.finish()
+    }
+
+    let a = glib::Object::new::<Compared>(&[]).unwrap();
+    let b = glib::Object::new::<Compared>(&[]).unwrap();
+    a.set_my_str("config".into());
+    b.set_my_str("config".into());
+    a.set_writable_i32(1);
+    assert!(a.props_eq(&b));
+    assert_eq!(digest(&a), digest(&b));
+
+    a.set_my_f64(0.25);
+    assert!(!a.props_eq(&b));
+    b.set_my_f64(0.25);
+    assert!(a.props_eq(&b));
+
+    a.set_my_strv(vec!["one".into(), "two".into()]);
+    assert!(!a.props_eq(&b));
+    b.set_my_strv(vec!["one".into(), "two".into()]);
+    assert!(a.props_eq(&b));
+    assert_eq!(digest(&a), digest(&b));
+
+    b.set_my_i32(7);
+    assert!(!a.props_eq(&b));
+    assert_ne!(digest(&a), digest(&b));
+}
+
+#[test]
+fn duplicate() {
+    use glib::once_cell::unsync::OnceCell;
+
+    wrapper!(Leaf(LeafPrivate));
+    #[object_impl(final, type = Leaf)]
+    impl ObjectImpl for LeafPrivate {
+        properties! {
+            #[derive(Default)]
+            pub struct LeafPrivate {
+                #[property(get, set)]
+                label: RefCell<String>,
+            }
+        }
+    }
+
+    wrapper!(Duplicated(DuplicatedPrivate));
+    #[object_impl(trait = DuplicatedExt, duplicate)]
+    impl ObjectImpl for DuplicatedPrivate {
+        properties! {
+            #[derive(Default)]
+            pub struct DuplicatedPrivate {
+                #[property(get, set)]
+                my_i32: Cell<i32>,
+                #[property(get, set, construct_only)]
+                id: OnceCell<u64>,
+                #[property(get, set, object)]
+                leaf: RefCell<Option<Leaf>>,
+                #[property(get, set, object)]
+                spare: RefCell<Option<Leaf>>,
+                #[property(get, set, object)]
+                peer: RefCell<Option<Duplicated>>,
+                #[property(get)]
+                read_only: Cell<u32>,
+            }
+        }
+    }
+
+    let leaf = glib::Object::new::<Leaf>(&[("label", &"original")]).unwrap();
+    let obj = glib::Object::new::<Duplicated>(&[("id", &42u64), ("leaf", &leaf)]).unwrap();
+    obj.set_my_i32(5);
+
+    let copy = obj.duplicate();
+    assert_ne!(copy, obj);
+    assert_eq!(copy.my_i32(), 5);
+    assert_eq!(copy.id(), 42);
+    assert_eq!(copy.leaf().as_ref(), Some(&leaf));
+
+    let deep = obj.duplicate_deep();
+    assert_eq!(deep.id(), 42);
+    let deep_leaf = deep.leaf().unwrap();
+    assert_ne!(deep_leaf, leaf);
+    assert_eq!(deep_leaf.label(), "original");
+    leaf.set_label("changed".into());
+    assert_eq!(deep_leaf.label(), "original");
+
+    obj.set_spare(Some(leaf.clone()));
+    obj.set_peer(Some(obj.clone()));
+    let deep = obj.duplicate_deep();
+    assert_eq!(deep.leaf(), deep.spare());
+    assert_ne!(deep.leaf().as_ref(), Some(&leaf));
+    let peer = deep.peer().unwrap();
+    assert_ne!(peer, obj);
+    assert_eq!(peer.peer().as_ref(), Some(&obj));
+    obj.set_peer(None);
+    peer.set_peer(None);
+}
+
+#[test]
+fn history() {
+    wrapper!(Edited(EditedPrivate));
+    #[object_impl(trait = EditedExt, history)]
+    impl ObjectImpl for EditedPrivate {
+        properties! {
+            #[derive(Default)]
+            pub struct EditedPrivate {
+                #[property(get, set)]
+                my_i32: Cell<i32>,
+                #[property(get, set)]
+                my_str: RefCell<String>,
+                #[property(get, set, construct, default = 3)]
+                level: Cell<i32>,
+                #[property(computed, get, set)]
+                summary: String,
+            }
+        }
+    }
+
+    impl Edited {
+        fn summary(&self) -> String {
+            let mark = if self.can_undo() { "*" } else { "" };
+            format!("{}{}", self.my_str(), mark)
+        }
+        fn _set_summary(&self, value: String) {
+            self.set_my_str(value.trim_end_matches('*').into());
+        }
+    }
+
+    let obj = glib::Object::new::<Edited>(&[("level", &5i32)]).unwrap();
+    assert_eq!(obj.level(), 5);
+    let notifies = std::rc::Rc::new(Cell::new(0));
+    obj.connect_my_i32_notify(glib::clone!(@strong notifies => move |_| {
+        notifies.set(notifies.get() + 1);
+    }));
+    assert!(!obj.can_undo());
+    assert!(!obj.undo());
+
+    obj.set_my_i32(1);
+    obj.set_my_i32(1);
+    obj.set_property("my-i32", 2i32);
+    obj.begin_group();
+    obj.set_my_i32(3);
+    obj.set_my_str("grouped".into());
+    obj.end_group();
+    assert_eq!(notifies.get(), 4);
+
+    assert!(obj.undo());
+    assert_eq!(obj.my_i32(), 2);
+    assert_eq!(obj.my_str(), "");
+    assert!(obj.undo());
+    assert_eq!(obj.my_i32(), 1);
+    assert_eq!(notifies.get(), 6);
+    assert!(obj.can_redo());
+
+    assert!(obj.redo());
+    assert_eq!(obj.my_i32(), 2);
+    obj.set_my_i32(10);
+    assert!(!obj.can_redo());
+
+    assert!(obj.undo());
+    assert!(obj.undo());
+    assert!(obj.undo());
+    assert_eq!(obj.my_i32(), 0);
+    assert!(!obj.undo());
+
+    obj.set_level(6);
+    assert!(obj.undo());
+    assert_eq!(obj.level(), 5);
+    assert!(!obj.undo());
+
+    obj.set_my_i32(4);
+    obj.begin_group();
+    obj.set_my_i32(5);
+    obj.clear_history();
+    assert!(!obj.can_undo());
+    obj.set_my_i32(6);
+    assert!(obj.undo());
+    assert_eq!(obj.my_i32(), 5);
+
+    obj.clear_history();
+    obj.set_summary("draft".into());
+    assert_eq!(obj.summary(), "draft*");
+    assert!(obj.undo());
+    assert_eq!(obj.my_str(), "");
+}
+
+#[test]
+fn transaction() {
+    use gobject_impl::PropertyError;
+
+    wrapper!(Transacted(TransactedPrivate));
+    #[object_impl(trait = TransactedExt, transaction)]
+    impl ObjectImpl for TransactedPrivate {
+        properties! {
+            #[derive(Default)]
+            pub struct TransactedPrivate {
+                #[property(get, set)]
+                my_i32: Cell<i32>,
+                #[property(get, set)]
+                my_str: RefCell<String>,
+                #[property(get, set)]
+                my_u32: Mutex<u32>,
+                #[property(get, set, construct, minimum = 1, maximum = 10, default = 1)]
+                level: Cell<u8>,
+                #[property(get, set = _)]
+                custom: Cell<i32>,
+            }
+        }
+    }
+
+    impl Transacted {
+        fn _set_custom(&self, value: i32) {
+            self.imp().custom.set(value * 2);
+            self.notify_custom();
+        }
+    }
+
+    let obj = glib::Object::new::<Transacted>(&[]).unwrap();
+    let notifies = std::rc::Rc::new(RefCell::new(Vec::new()));
+    obj.connect_notify_local(
+        None,
+        glib::clone!(@strong notifies => move |_, pspec| {
+            notifies.borrow_mut().push(pspec.name().to_owned());
+        }),
+    );
+
+    let res = obj.transaction(|tx| {
+        tx.set_my_i32(1);
+        tx.set_my_i32(2);
+        tx.set_my_str("committed".into());
+        tx.set_my_u32(0);
+        Ok::<_, ()>(())
+    });
+    assert_eq!(res, Ok(()));
+    assert_eq!(obj.my_i32(), 2);
+    assert_eq!(obj.my_str(), "committed");
+    notifies.borrow_mut().sort();
+    assert_eq!(*notifies.borrow(), ["my-i32", "my-str"]);
+
+    notifies.borrow_mut().clear();
+    let res = obj.transaction(|tx| {
+        tx.set_my_i32(3);
+        tx.set_my_str("discarded".into());
+        tx.set_my_u32(4);
+        tx.set_my_i32(5);
+        Err::<(), _>("invalid")
+    });
+    assert_eq!(res, Err("invalid"));
+    assert_eq!(obj.my_i32(), 2);
+    assert_eq!(obj.my_str(), "committed");
+    assert_eq!(obj.my_u32(), 0);
+    assert!(notifies.borrow().is_empty());
+
+    let res = obj.transaction(|tx| {
+        tx.set_my_i32(6);
+        tx.set_level(5)?;
+        tx.set_level(11)
+    });
+    assert!(matches!(res, Err(PropertyError::InvalidValue { .. })));
+    assert_eq!(obj.my_i32(), 2);
+    assert_eq!(obj.level(), 1);
+    assert!(notifies.borrow().is_empty());
+
+    let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        obj.transaction(|tx| {
+            tx.set_my_i32(7);
+            tx.set_my_str("unwound".into());
+            panic!("aborted");
+            #[allow(unreachable_code)]
+            Ok::<_, ()>(())
+        })
+    }));
+    assert!(res.is_err());
+    assert_eq!(obj.my_i32(), 2);
+    assert_eq!(obj.my_str(), "committed");
+    assert!(notifies.borrow().is_empty());
+
+    obj.set_custom(3);
+    assert_eq!(obj.custom(), 6);
+}
+
+#[test]
+fn seal() {
+    use gobject_impl::{PropertyError, PropertyFromStrExt};
+
+    wrapper!(Sealed(SealedPrivate));
+    #[object_impl(trait = SealedExt, seal, transaction)]
+    impl ObjectImpl for SealedPrivate {
+        properties! {
+            #[derive(Default)]
+            pub struct SealedPrivate {
+                #[property(get, set)]
+                my_i32: Cell<i32>,
+                #[property(get, set, set_inline)]
+                my_str: RefCell<String>,
+            }
+        }
+    }
+
+    let obj = glib::Object::new::<Sealed>(&[]).unwrap();
+    obj.set_my_i32(1);
+    obj.set_my_str("setup".into());
+    assert!(!obj.is_sealed());
+    obj.seal();
+    assert!(obj.is_sealed());
+    let notified = std::rc::Rc::new(Cell::new(0));
+    obj.connect_notify_local(None, {
+        let notified = notified.clone();
+        move |_, _| notified.set(notified.get() + 1)
+    });
+
+    obj.set_my_i32(2);
+    obj.set_my_str("changed".into());
+    assert_eq!(notified.get(), 0);
+    obj.set_property("my-i32", 3i32);
+    obj.transaction(|tx| {
+        tx.set_my_i32(4);
+        Ok::<_, ()>(())
+    })
+    .unwrap();
+    assert_eq!(obj.my_i32(), 1);
+    assert_eq!(obj.my_str(), "setup");
+    assert!(matches!(
+        obj.set_property_from_str("my-i32", "5"),
+        Err(PropertyError::Sealed { .. })
+    ));
+}
+
+#[test]
+fn pspec_dispatch() {
+    use gobject_impl::ObjectPspecExt;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    wrapper!(Dispatched(DispatchedPrivate));
+    #[object_impl(final, type = Dispatched)]
+    impl ObjectImpl for DispatchedPrivate {
+        properties! {
+            #[derive(Default)]
+            pub struct DispatchedPrivate {
+                #[property(get, set = _)]
+                first: Cell<i32>,
+                #[property(get, set)]
+                second: Cell<i32>,
+                #[property(get)]
+                read_only: Cell<i32>,
+                #[property(get, set, construct_only)]
+                fixed: Cell<i32>,
+                log: RefCell<Vec<String>>,
+            }
+        }
+    }
+    impl Dispatched {
+        fn _set_first(&self, value: i32) {
+            self.imp().first.set(value);
+            self.set_second(value * 2);
+            assert!(self.imp().log.borrow().is_empty());
+        }
+    }
+
+    let obj = glib::Object::new::<Dispatched>(&[("fixed", &3i32)]).unwrap();
+    obj.connect_notify_local(None, |obj, pspec| {
+        obj.imp().log.borrow_mut().push(pspec.name().to_owned());
+    });
+    obj.set_first(4);
+    assert_eq!(obj.second(), 8);
+    obj.imp().log.borrow_mut().sort();
+    assert_eq!(*obj.imp().log.borrow(), ["first", "second"]);
+    obj.imp().log.borrow_mut().clear();
+
+    obj.set_property_from_pspec(Dispatched::pspec_second(), 5i32.to_value());
+    assert_eq!(
+        obj.property_from_pspec(Dispatched::pspec_second()).get(),
+        Ok(5)
+    );
+    assert_eq!(*obj.imp().log.borrow(), ["second"]);
+
+    let read_only = catch_unwind(AssertUnwindSafe(|| {
+        obj.set_property_from_pspec(Dispatched::pspec_read_only(), 1i32.to_value())
+    }));
+    assert!(read_only.is_err());
+    let fixed = catch_unwind(AssertUnwindSafe(|| {
+        obj.set_property_from_pspec(Dispatched::pspec_fixed(), 1i32.to_value())
+    }));
+    assert!(fixed.is_err());
+    assert_eq!(obj.fixed(), 3);
+    assert_eq!(obj.read_only(), 0);
+}
@@ -0,0 +1,140 @@
+use glib::prelude::*;
+use glib::subclass::prelude::*;
+use gobject_impl::object_impl;
+use std::cell::{Cell, RefCell};
+
+macro_rules! wrapper {
+    ($name:ident($priv:ident)) => {
+        glib::wrapper! {
+            pub struct $name(ObjectSubclass<$priv>);
+        }
+        #[glib::object_subclass]
+        impl ObjectSubclass for $priv {
+            const NAME: &'static str = stringify!($name);
+            type Type = $name;
+        }
+    };
+}
+
+#[test]
+fn serde_roundtrip() {
+    use glib::once_cell::unsync::OnceCell;
+
+    #[derive(Debug, Default, Eq, PartialEq, Clone, Copy, glib::Enum)]
+    #[repr(u32)]
+    #[enum_type(name = "SerdeFruitType")]
+    pub enum Fruit {
+        #[default]
+        #[enum_value(nick = "apple")]
+        Apple,
+        #[enum_value(nick = "pear")]
+        Pear,
+    }
+
+    wrapper!(Child(ChildPrivate));
+    #[object_impl(final, type = Child, serde)]
+    impl ObjectImpl for ChildPrivate {
+        properties! {
+            #[derive(Default)]
+            pub struct ChildPrivate {
+                #[property(get, set)]
+                label: RefCell<String>,
+            }
+        }
+    }
+
+    wrapper!(Serialized(SerializedPrivate));
+    impl Default for SerializedPrivate {
+        fn default() -> Self {
+            Self {
+                my_i32: Default::default(),
+                my_str: Default::default(),
+                fruit: Default::default(),
+                binding_flags: Cell::new(glib::BindingFlags::empty()),
+                variant: Default::default(),
+                child: Default::default(),
+                read_only: Default::default(),
+            }
+        }
+    }
+    #[object_impl(final, type = Serialized, serde)]
+    impl ObjectImpl for SerializedPrivate {
+        properties! {
+            pub struct SerializedPrivate {
+                #[property(get, set)]
+                my_i32: Cell<i32>,
+                #[property(get, set)]
+                my_str: RefCell<Option<String>>,
+                #[property(get, set, enum)]
+                fruit: Cell<Fruit>,
+                #[property(get, set, flags)]
+                binding_flags: Cell<glib::BindingFlags>,
+                #[property(get, set, variant = "(is)")]
+                variant: RefCell<Option<glib::Variant>>,
+                #[property(get, set, object, construct_only)]
+                child: OnceCell<Option<Child>>,
+                #[property(get)]
+                read_only: Cell<u32>,
+            }
+        }
+    }
+
+    let child = glib::Object::new::<Child>(&[("label", &"kid")]).unwrap();
+    let obj = glib::Object::new::<Serialized>(&[("child", &child)]).unwrap();
+    obj.set_my_i32(-7);
+    obj.set_my_str(Some("hello".into()));
+    obj.set_fruit(Fruit::Pear);
+    obj.set_binding_flags(glib::BindingFlags::BIDIRECTIONAL | glib::BindingFlags::SYNC_CREATE);
+    obj.set_variant(Some((5i32, "five").to_variant()));
+
+    let json = serde_json::to_value(&obj).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "my-i32": -7,
+            "my-str": "hello",
+            "fruit": "pear",
+            "binding-flags": "bidirectional|sync-create",
+            "variant": "(5, 'five')",
+            "child": { "label": "kid" },
+            "read-only": 0,
+        })
+    );
+
+    let copy = serde_json::from_value::<Serialized>(json).unwrap();
+    assert_eq!(copy.my_i32(), -7);
+    assert_eq!(copy.my_str().as_deref(), Some("hello"));
+    assert_eq!(copy.fruit(), Fruit::Pear);
+    assert_eq!(copy.binding_flags(), obj.binding_flags());
+    assert_eq!(copy.variant(), obj.variant());
+    assert_eq!(copy.child().unwrap().label(), "kid");
+
+    let empty = serde_json::from_str::<Serialized>(r#"{"child": null}"#).unwrap();
+    assert!(empty.child().is_none());
+    assert_eq!(empty.fruit(), Fruit::Apple);
+
+    let err = serde_json::from_str::<Serialized>(r#"{"bogus": 1}"#).unwrap_err();
+    assert!(err.to_string().contains("bogus"));
+    let err = serde_json::from_str::<Serialized>(r#"{"fruit": "kiwi"}"#).unwrap_err();
+    assert!(err.to_string().contains("kiwi"));
+
+    wrapper!(Linked(LinkedPrivate));
+    #[object_impl(final, type = Linked, serde)]
+    impl ObjectImpl for LinkedPrivate {
+        properties! {
+            #[derive(Default)]
+            pub struct LinkedPrivate {
+                #[property(get, set, object)]
+                peer: RefCell<Option<Linked>>,
+            }
+        }
+    }
+    let a = glib::Object::new::<Linked>(&[]).unwrap();
+    let b = glib::Object::new::<Linked>(&[]).unwrap();
+    a.set_peer(Some(b.clone()));
+    assert!(serde_json::to_value(&a).is_ok());
+    b.set_peer(Some(a.clone()));
+    let err = serde_json::to_value(&a).unwrap_err();
+    assert!(err.to_string().contains("cycle"));
+    a.set_peer(None);
+}
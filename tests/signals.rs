@@ -27,7 +27,7 @@ fn signals() {
         fn noparam(&self) {}
         #[signal]
         fn param(&self, hello: i32) {}
-        #[signal]
+        #[signal(!connect_after)]
         fn twoparams(&self, hello: i32, world: String) {}
         #[signal(run_last)]
         fn with_handler(&self, _hello: i32, world: String) {
@@ -84,6 +84,16 @@ fn signals() {
         &["noparam", "handler", "handler last"]
     );
 
+    signals.imp().log.borrow_mut().clear();
+    signals.connect_with_handler_after(|sig, _, world| {
+        sig.imp().append(&(world + " after"));
+    });
+    signals.emit_with_handler(500, "handler".into());
+    assert_eq!(
+        *signals.imp().log.borrow(),
+        &["handler", "handler last", "handler after"]
+    );
+
     assert_eq!(signals.emit_with_retval(10), 15);
     signals.connect_with_retval(|_, val| val * 2);
     assert_eq!(signals.emit_with_retval(10), 20);
@@ -97,4 +107,486 @@ fn signals() {
     signals.connect_has_detail(Some("hello".into()), |_, val| val * 3);
     assert_eq!(signals.emit_has_detail(None, 20), 27);
     assert_eq!(signals.emit_has_detail(Some("hello".into()), 20), 287);
+    signals.connect_has_detail_after(Some("other".into()), |_, val| val);
+    assert_eq!(signals.emit_has_detail(Some("other".into()), 20), 47);
+}
+
+#[test]
+fn sync_signals() {
+    use std::sync::atomic::{AtomicI32, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    glib::wrapper! {
+        pub struct SyncSignals(ObjectSubclass<SyncSignalsPrivate>);
+    }
+    unsafe impl Send for SyncSignals {}
+    unsafe impl Sync for SyncSignals {}
+    #[glib::object_subclass]
+    impl ObjectSubclass for SyncSignalsPrivate {
+        const NAME: &'static str = "SyncSignals";
+        type Type = SyncSignals;
+    }
+    #[object_impl(final, type = SyncSignals, sync)]
+    impl ObjectImpl for SyncSignalsPrivate {
+        properties! {
+            #[derive(Default)]
+            pub struct SyncSignalsPrivate {
+                #[property(get, set)]
+                count: Mutex<i32>,
+            }
+        }
+        #[signal]
+        fn added(&self, val: i32) {}
+    }
+
+    let obj = glib::Object::new::<SyncSignals>(&[]).unwrap();
+    let total = Arc::new(AtomicI32::new(0));
+    obj.connect_added({
+        let total = total.clone();
+        move |_, val| {
+            total.fetch_add(val, Ordering::SeqCst);
+        }
+    });
+    obj.connect_count_notify({
+        let total = total.clone();
+        move |obj| {
+            total.fetch_add(obj.count() * 100, Ordering::SeqCst);
+        }
+    });
+    std::thread::spawn({
+        let obj = obj.clone();
+        move || {
+            obj.emit_added(2);
+            obj.set_count(1);
+        }
+    })
+    .join()
+    .unwrap();
+    assert_eq!(total.load(Ordering::SeqCst), 102);
+}
+
+#[test]
+fn handler_guards() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    glib::wrapper! {
+        pub struct Guarded(ObjectSubclass<GuardedPrivate>);
+    }
+    #[glib::object_subclass]
+    impl ObjectSubclass for GuardedPrivate {
+        const NAME: &'static str = "Guarded";
+        type Type = Guarded;
+    }
+    #[derive(Default)]
+    pub struct GuardedPrivate {}
+    #[object_impl(final, type = Guarded)]
+    impl ObjectImpl for GuardedPrivate {
+        #[signal]
+        fn ping(&self) {}
+        #[signal]
+        fn pong(&self) {}
+    }
+
+    let obj = glib::Object::new::<Guarded>(&[]).unwrap();
+    let count = Rc::new(Cell::new(0));
+
+    let id = obj.connect_ping_typed({
+        let count = count.clone();
+        move |_| count.set(count.get() + 1)
+    });
+    obj.emit_ping();
+    {
+        let _blocked = obj.block_ping(&id);
+        obj.emit_ping();
+    }
+    obj.emit_ping();
+    assert_eq!(count.get(), 2);
+    obj.disconnect_ping(id);
+    obj.emit_ping();
+    assert_eq!(count.get(), 2);
+
+    {
+        let _guard = obj.connect_pong_scoped({
+            let count = count.clone();
+            move |_| count.set(count.get() + 10)
+        });
+        obj.emit_pong();
+    }
+    obj.emit_pong();
+    assert_eq!(count.get(), 12);
+
+    let id: glib::SignalHandlerId = obj.connect_pong(|_| {});
+    glib::ObjectExt::disconnect(&obj, id);
+    let id: glib::SignalHandlerId = obj.connect_pong_typed(|_| {}).into();
+    glib::ObjectExt::disconnect(&obj, id);
+
+    let id: glib::SignalHandlerId = obj.connect_pong_after(|_| {});
+    glib::ObjectExt::disconnect(&obj, id);
+    let id = obj.connect_pong_after_typed({
+        let count = count.clone();
+        move |_| count.set(count.get() + 100)
+    });
+    obj.emit_pong();
+    obj.disconnect_pong(id);
+    obj.emit_pong();
+    assert_eq!(count.get(), 112);
+}
+
+#[test]
+fn weak_connect() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    glib::wrapper! {
+        pub struct Watched(ObjectSubclass<WatchedPrivate>);
+    }
+    #[glib::object_subclass]
+    impl ObjectSubclass for WatchedPrivate {
+        const NAME: &'static str = "Watched";
+        type Type = Watched;
+    }
+    #[object_impl(final, type = Watched)]
+    impl ObjectImpl for WatchedPrivate {
+        properties! {
+            #[derive(Default)]
+            pub struct WatchedPrivate {
+                #[property(get, set)]
+                count: Cell<i32>,
+            }
+        }
+        #[signal(run_last)]
+        fn doubled(&self, val: i32) -> i32 {
+            val * 2
+        }
+    }
+
+    let obj = glib::Object::new::<Watched>(&[]).unwrap();
+    let other = glib::Object::new::<glib::Object>(&[]).unwrap();
+    let calls = Rc::new(Cell::new(0));
+
+    obj.connect_doubled_with_weak(&other, {
+        let calls = calls.clone();
+        move |_, _, val| {
+            calls.set(calls.get() + 1);
+            val * 3
+        }
+    });
+    let id: glib::SignalHandlerId = obj.connect_doubled_with_weak(&other, |_, _, val| val);
+    glib::ObjectExt::disconnect(&obj, id);
+    let id = obj.connect_doubled_with_weak_typed(&other, |_, _, val| val);
+    obj.disconnect_doubled(id);
+    obj.connect_count_notify_with_weak(&other, {
+        let calls = calls.clone();
+        move |obj, _| calls.set(calls.get() + obj.count())
+    });
+    assert_eq!(obj.emit_doubled(2), 4);
+    obj.set_count(10);
+    assert_eq!(calls.get(), 11);
+
+    drop(other);
+    assert_eq!(obj.emit_doubled(2), 4);
+    obj.set_count(20);
+    assert_eq!(calls.get(), 11);
+}
+
+#[test]
+fn signal_future() {
+    glib::wrapper! {
+        pub struct Awaited(ObjectSubclass<AwaitedPrivate>);
+    }
+    #[glib::object_subclass]
+    impl ObjectSubclass for AwaitedPrivate {
+        const NAME: &'static str = "Awaited";
+        type Type = Awaited;
+    }
+    #[derive(Default)]
+    pub struct AwaitedPrivate {}
+    #[object_impl(final, type = Awaited)]
+    impl ObjectImpl for AwaitedPrivate {
+        #[signal(future)]
+        fn done(&self) {}
+        #[signal(detailed, future)]
+        fn progress(&self, step: u32, label: String) {}
+    }
+
+    let obj = glib::Object::new::<Awaited>(&[]).unwrap();
+    let ctx = glib::MainContext::default();
+
+    let done = obj.done_future();
+    obj.emit_done();
+    assert_eq!(ctx.block_on(done), Some(()));
+
+    let progress = obj.progress_future(Some("files".into()));
+    ctx.spawn_local(glib::clone!(@strong obj => async move {
+        obj.emit_progress(Some("other".into()), 1, "skipped".into());
+        obj.emit_progress(Some("files".into()), 2, "copied".into());
+        obj.emit_progress(Some("files".into()), 3, "ignored".into());
+    }));
+    assert_eq!(ctx.block_on(progress), Some((2, "copied".to_owned())));
+
+    let done = obj.done_future();
+    ctx.spawn_local(async move {
+        drop(obj);
+    });
+    assert_eq!(ctx.block_on(done), None);
+}
+
+#[test]
+fn async_handlers() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    glib::wrapper! {
+        pub struct Spawning(ObjectSubclass<SpawningPrivate>);
+    }
+    #[glib::object_subclass]
+    impl ObjectSubclass for SpawningPrivate {
+        const NAME: &'static str = "Spawning";
+        type Type = Spawning;
+    }
+    #[derive(Default)]
+    pub struct SpawningPrivate {}
+    #[object_impl(final, type = Spawning)]
+    impl ObjectImpl for SpawningPrivate {
+        #[signal(connect_async)]
+        fn started(&self, job: u32) {}
+    }
+
+    struct DropFlag(Rc<Cell<u32>>);
+    impl Drop for DropFlag {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    let obj = glib::Object::new::<Spawning>(&[]).unwrap();
+    let started = Rc::new(RefCell::new(Vec::new()));
+    let dropped = Rc::new(Cell::new(0));
+    let ctx = glib::MainContext::new();
+    ctx.with_thread_default(|| {
+        let id = obj.connect_started_async({
+            let started = started.clone();
+            let dropped = dropped.clone();
+            move |_, job| {
+                let started = started.clone();
+                let flag = DropFlag(dropped.clone());
+                async move {
+                    let _flag = flag;
+                    started.borrow_mut().push(job);
+                    std::future::pending::<()>().await;
+                }
+            }
+        });
+        obj.emit_started(1);
+        obj.emit_started(2);
+        assert!(started.borrow().is_empty());
+        while ctx.iteration(false) {}
+        assert_eq!(*started.borrow(), [1, 2]);
+        assert_eq!(dropped.get(), 0);
+
+        obj.disconnect_started(id);
+        assert_eq!(dropped.get(), 2);
+    })
+    .unwrap();
+}
+
+#[test]
+fn emission_hooks() {
+    use glib::ObjectType;
+    use std::sync::{Arc, Mutex};
+
+    glib::wrapper! {
+        pub struct Hooked(ObjectSubclass<HookedPrivate>);
+    }
+    #[glib::object_subclass]
+    impl ObjectSubclass for HookedPrivate {
+        const NAME: &'static str = "Hooked";
+        type Type = Hooked;
+    }
+    #[derive(Default)]
+    pub struct HookedPrivate {}
+    #[object_impl(final, type = Hooked)]
+    impl ObjectImpl for HookedPrivate {
+        #[signal]
+        fn clicked(&self, count: u32) {}
+        #[signal(detailed)]
+        fn changed(&self) {}
+        #[signal(no_hooks)]
+        fn hidden(&self) {}
+    }
+
+    let first = glib::Object::new::<Hooked>(&[]).unwrap();
+    let second = glib::Object::new::<Hooked>(&[]).unwrap();
+    let clicks = Arc::new(Mutex::new(Vec::new()));
+    let id = Hooked::add_clicked_emission_hook({
+        let clicks = clicks.clone();
+        move |obj, count| {
+            clicks.lock().unwrap().push((obj.as_ptr() as usize, count));
+            true
+        }
+    });
+    first.emit_clicked(1);
+    second.emit_clicked(2);
+    assert_eq!(
+        *clicks.lock().unwrap(),
+        [(first.as_ptr() as usize, 1), (second.as_ptr() as usize, 2)]
+    );
+    id.remove();
+    first.emit_clicked(3);
+    assert_eq!(clicks.lock().unwrap().len(), 2);
+    clicks.lock().unwrap().clear();
+
+    let changes = Arc::new(Mutex::new(0));
+    Hooked::add_changed_emission_hook(Some(glib::Quark::from_str("size")), {
+        let changes = changes.clone();
+        move |_| {
+            *changes.lock().unwrap() += 1;
+            false
+        }
+    });
+    first.emit_changed(Some(glib::Quark::from_str("color")));
+    assert_eq!(*changes.lock().unwrap(), 0);
+    first.emit_changed(Some(glib::Quark::from_str("size")));
+    first.emit_changed(Some(glib::Quark::from_str("size")));
+    assert_eq!(*changes.lock().unwrap(), 1);
+    first.emit_hidden();
+}
+
+#[test]
+fn stop_emission() {
+    use glib::signal::Inhibit;
+    use std::rc::Rc;
+
+    glib::wrapper! {
+        pub struct Propagating(ObjectSubclass<PropagatingPrivate>);
+    }
+    #[glib::object_subclass]
+    impl ObjectSubclass for PropagatingPrivate {
+        const NAME: &'static str = "Propagating";
+        type Type = Propagating;
+    }
+    #[derive(Default)]
+    pub struct PropagatingPrivate {}
+    #[object_impl(final, type = Propagating)]
+    impl ObjectImpl for PropagatingPrivate {
+        #[signal(run_last, propagation)]
+        fn key_pressed(&self, key: u32) -> bool {
+            key == 0
+        }
+        #[signal(detailed)]
+        fn changed(&self) {}
+    }
+
+    let obj = glib::Object::new::<Propagating>(&[]).unwrap();
+    let calls = Rc::new(RefCell::new(Vec::new()));
+    for (name, handled) in [("first", false), ("second", true), ("third", true)] {
+        let calls = calls.clone();
+        obj.connect_key_pressed(move |_, key| {
+            calls.borrow_mut().push((name, key));
+            Inhibit(handled && key > 1)
+        });
+    }
+    assert!(obj.emit_key_pressed(5));
+    assert_eq!(*calls.borrow(), [("first", 5), ("second", 5)]);
+    calls.borrow_mut().clear();
+    assert!(!obj.emit_key_pressed(1));
+    assert_eq!(calls.borrow().len(), 3);
+    calls.borrow_mut().clear();
+    assert!(obj.emit_key_pressed(0));
+
+    let changes = Rc::new(RefCell::new(Vec::new()));
+    let record = |obj: &Propagating| {
+        let changes = changes.clone();
+        obj.connect_changed(None, move |_| changes.borrow_mut().push("after stop"))
+    };
+    let size = glib::Quark::from_str("size");
+    let stop = obj.connect_changed(Some(size), move |obj| {
+        obj.stop_changed_emission_detailed(size);
+    });
+    let recorder = record(&obj);
+    obj.emit_changed(Some(size));
+    assert!(changes.borrow().is_empty());
+    obj.emit_changed(None);
+    assert_eq!(*changes.borrow(), ["after stop"]);
+
+    glib::ObjectExt::disconnect(&obj, stop);
+    glib::ObjectExt::disconnect(&obj, recorder);
+    changes.borrow_mut().clear();
+    obj.connect_changed(None, |obj| obj.stop_changed_emission());
+    record(&obj);
+    obj.emit_changed(None);
+    assert!(changes.borrow().is_empty());
+}
+
+#[test]
+fn accumulator_presets() {
+    glib::wrapper! {
+        pub struct Accumulating(ObjectSubclass<AccumulatingPrivate>);
+    }
+    #[glib::object_subclass]
+    impl ObjectSubclass for AccumulatingPrivate {
+        const NAME: &'static str = "Accumulating";
+        type Type = Accumulating;
+    }
+    #[derive(Default)]
+    pub struct AccumulatingPrivate {}
+    #[object_impl(final, type = Accumulating)]
+    impl ObjectImpl for AccumulatingPrivate {
+        #[signal(run_last, accumulator = first_wins)]
+        fn pick(&self, fallback: u32) -> u32 {
+            fallback
+        }
+        #[signal(run_last, accumulator = true_handled)]
+        fn activate(&self) -> bool {
+            true
+        }
+        #[signal(run_first, accumulator = sum)]
+        fn weight(&self, base: f64) -> f64 {
+            base
+        }
+        #[signal(run_first, accumulator = sum)]
+        fn count(&self) -> u8 {
+            200
+        }
+        #[signal(run_last, accumulator = collect)]
+        fn names(&self, prefix: String) -> String {
+            prefix + "class"
+        }
+    }
+
+    let obj = glib::Object::new::<Accumulating>(&[]).unwrap();
+    assert_eq!(obj.emit_pick(9), 9);
+    assert!(obj.emit_activate());
+    assert_eq!(obj.emit_weight(0.5), 0.5);
+    assert_eq!(obj.emit_names("a-".into()), ["a-class"]);
+
+    obj.connect_pick(|_, _| 1);
+    obj.connect_pick(|_, _| 2);
+    assert_eq!(obj.emit_pick(9), 1);
+
+    let activated = std::rc::Rc::new(std::cell::Cell::new(0));
+    for handled in [false, true] {
+        let activated = activated.clone();
+        obj.connect_activate(move |_| {
+            activated.set(activated.get() + 1);
+            handled
+        });
+    }
+    assert!(obj.emit_activate());
+    assert_eq!(activated.get(), 2);
+
+    obj.connect_weight(|_, base| base * 2.0);
+    obj.connect_weight(|_, _| 3.0);
+    assert_eq!(obj.emit_weight(0.5), 4.5);
+
+    obj.connect_count(|_| 100);
+    assert_eq!(obj.emit_count(), 44);
+
+    obj.connect_names(|_, prefix| prefix + "first");
+    obj.connect_names_after(|_, prefix| prefix + "after");
+    assert_eq!(
+        obj.emit_names("b-".into()),
+        ["b-first", "b-class", "b-after"]
+    );
 }
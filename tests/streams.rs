@@ -0,0 +1,56 @@
+use futures_util::StreamExt;
+use glib::subclass::prelude::*;
+use gobject_impl::object_impl;
+use std::cell::{Cell, RefCell};
+
+#[test]
+fn streams() {
+    glib::wrapper! {
+        pub struct Streamed(ObjectSubclass<StreamedPrivate>);
+    }
+    #[glib::object_subclass]
+    impl ObjectSubclass for StreamedPrivate {
+        const NAME: &'static str = "Streamed";
+        type Type = Streamed;
+    }
+    #[object_impl(final, type = Streamed)]
+    impl ObjectImpl for StreamedPrivate {
+        properties! {
+            #[derive(Default)]
+            pub struct StreamedPrivate {
+                #[property(get, set)]
+                count: Cell<i32>,
+                #[property(get, set)]
+                label: RefCell<String>,
+            }
+        }
+        #[signal(detailed)]
+        fn message(&self, text: String) {}
+    }
+
+    let obj = glib::Object::new::<Streamed>(&[]).unwrap();
+    let ctx = glib::MainContext::default();
+
+    let messages = obj.message_stream(None);
+    let counts = obj.count_stream();
+    obj.emit_message(None, "one".into());
+    obj.set_count(1);
+    obj.set_label("ignored".into());
+    obj.emit_message(Some("detail".into()), "two".into());
+    obj.set_count(2);
+
+    let messages = ctx.block_on(messages.take(2).collect::<Vec<_>>());
+    assert_eq!(messages, [("one".to_owned(),), ("two".to_owned(),)]);
+    let counts = ctx.block_on(counts.take(2).collect::<Vec<_>>());
+    assert_eq!(counts, [1, 2]);
+
+    let messages = obj.message_stream(None);
+    let counts = obj.count_stream();
+    obj.emit_message(None, "last".into());
+    obj.set_count(3);
+    drop(obj);
+    let messages = ctx.block_on(messages.collect::<Vec<_>>());
+    assert_eq!(messages, [("last".to_owned(),)]);
+    let counts = ctx.block_on(counts.collect::<Vec<_>>());
+    assert_eq!(counts, [3]);
+}
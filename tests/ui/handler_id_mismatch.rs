@@ -0,0 +1,26 @@
+use glib::subclass::prelude::*;
+use gobject_impl::object_impl;
+
+glib::wrapper! {
+    pub struct Guarded(ObjectSubclass<GuardedPrivate>);
+}
+#[glib::object_subclass]
+impl ObjectSubclass for GuardedPrivate {
+    const NAME: &'static str = "Guarded";
+    type Type = Guarded;
+}
+#[derive(Default)]
+pub struct GuardedPrivate {}
+#[object_impl(final, type = Guarded)]
+impl ObjectImpl for GuardedPrivate {
+    #[signal]
+    fn ping(&self) {}
+    #[signal]
+    fn pong(&self) {}
+}
+
+fn main() {
+    let obj = glib::Object::new::<Guarded>(&[]).unwrap();
+    let id = obj.connect_ping_after_typed(|_| {});
+    obj.disconnect_pong(id);
+}